@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, StatefulWidget};
+
+use crate::explorer::bookmarks::Bookmarks;
+
+/// Lists [`Bookmarks`] as `label  path`, with paths relative to the root.
+///
+/// The selected index of the [`ListState`] maps to a label with [`Bookmarks::label_at`],
+/// to jump there with [`Explorer::jump_to_bookmark`](crate::explorer::Explorer::jump_to_bookmark).
+#[derive(Debug, Clone)]
+pub struct BookmarksPanel<'bookmarks> {
+    bookmarks: &'bookmarks Bookmarks,
+    root: &'bookmarks Path,
+
+    block: Option<Block<'bookmarks>>,
+    /// Style used as a base style for the widget
+    style: Style,
+    label_style: Style,
+    highlight_style: Style,
+    highlight_symbol: &'bookmarks str,
+}
+
+impl<'bookmarks> BookmarksPanel<'bookmarks> {
+    /// Create a new `BookmarksPanel` showing the paths of the bookmarks relative to `root`.
+    #[must_use]
+    pub const fn new(bookmarks: &'bookmarks Bookmarks, root: &'bookmarks Path) -> Self {
+        Self {
+            bookmarks,
+            root,
+            block: None,
+            style: Style::new(),
+            label_style: Style::new(),
+            highlight_style: Style::new(),
+            highlight_symbol: "",
+        }
+    }
+
+    #[must_use]
+    pub fn block(mut self, block: Block<'bookmarks>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    #[must_use]
+    pub const fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn label_style(mut self, style: Style) -> Self {
+        self.label_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn highlight_symbol(mut self, highlight_symbol: &'bookmarks str) -> Self {
+        self.highlight_symbol = highlight_symbol;
+        self
+    }
+}
+
+impl StatefulWidget for BookmarksPanel<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let label_width = self
+            .bookmarks
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or_default();
+        let items = self.bookmarks.iter().map(|(label, path)| {
+            let relative = path.strip_prefix(self.root).unwrap_or(path);
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{label:<label_width$}"), self.label_style),
+                Span::raw("  "),
+                Span::raw(relative.to_string_lossy().to_string()),
+            ]))
+        });
+
+        let mut list = List::new(items)
+            .style(self.style)
+            .highlight_style(self.highlight_style)
+            .highlight_symbol(self.highlight_symbol);
+        if let Some(block) = self.block {
+            list = list.block(block);
+        }
+        StatefulWidget::render(list, area, buf, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_bookmarks() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.set("a", "/vault/notes/a.md").unwrap();
+        bookmarks.set("todo", "/vault/todo.md").unwrap();
+        bookmarks.set("x", "/tmp/x.md").unwrap();
+
+        let panel = BookmarksPanel::new(&bookmarks, Path::new("/vault")).highlight_symbol("> ");
+        let mut state = ListState::default().with_selected(Some(1));
+        let area = Rect::new(0, 0, 20, 3);
+        let mut buffer = Buffer::empty(area);
+        panel.render(area, &mut buffer, &mut state);
+        assert_eq!(
+            buffer,
+            Buffer::with_lines([
+                "  a     notes/a.md  ",
+                "> todo  todo.md     ",
+                "  x     /tmp/x.md   ",
+            ])
+        );
+        assert_eq!(bookmarks.label_at(1), Some("todo"));
+    }
+}
@@ -0,0 +1,267 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use std::path::Path;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::StatefulWidget;
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::tree::truncate::ELLIPSIS;
+
+/// Shows the path of the selected node like `root › dir › subdir › file`.
+///
+/// Build it from [`ExplorerState::selected`](crate::explorer::state::ExplorerState::selected).
+/// When the area is too narrow the segments in the middle are replaced by an ellipsis.
+/// Use [`BreadcrumbState::segment_at`] to find the clicked segment and
+/// [`ExplorerState::collapse_to`](crate::explorer::state::ExplorerState::collapse_to) to jump there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breadcrumb<'selected, Identifier> {
+    root: String,
+    selected: &'selected [Identifier],
+
+    /// Style used as a base style for the widget
+    style: Style,
+    /// Style of the last segment, the selected node itself
+    last_style: Style,
+    /// Style of the separators and the ellipsis
+    separator_style: Style,
+    separator: String,
+}
+
+/// Where the segments of a [`Breadcrumb`] were rendered on last render.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreadcrumbState<Identifier> {
+    /// Area of each visible segment and the identifier of the node it stands for.
+    /// The root segment has an empty identifier.
+    pub last_segments: Vec<(Rect, Vec<Identifier>)>,
+}
+
+impl<Identifier> Default for BreadcrumbState<Identifier> {
+    fn default() -> Self {
+        Self {
+            last_segments: Vec::new(),
+        }
+    }
+}
+
+impl<Identifier> BreadcrumbState<Identifier> {
+    /// Get the identifier of the segment rendered at the given position on last render.
+    ///
+    /// The root segment returns an empty identifier, the ellipsis is not a segment.
+    #[must_use]
+    pub fn segment_at(&self, position: Position) -> Option<&[Identifier]> {
+        self.last_segments
+            .iter()
+            .find(|(area, _)| area.contains(position))
+            .map(|(_, identifier)| identifier.as_slice())
+    }
+}
+
+impl<'selected, Identifier> Breadcrumb<'selected, Identifier>
+where
+    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+{
+    /// Create a new `Breadcrumb` for the selected node below a root labeled `root`.
+    #[must_use]
+    pub fn new(root: &str, selected: &'selected [Identifier]) -> Self {
+        Self {
+            root: root.to_string(),
+            selected,
+            style: Style::new(),
+            last_style: Style::new().add_modifier(Modifier::BOLD),
+            separator_style: Style::new(),
+            separator: " \u{203a} ".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub const fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn last_style(mut self, style: Style) -> Self {
+        self.last_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn separator_style(mut self, style: Style) -> Self {
+        self.separator_style = style;
+        self
+    }
+
+    #[must_use]
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Labels of all segments, starting with the root.
+    fn labels(&self) -> Vec<String> {
+        let names = self.selected.iter().map(|identifier| {
+            let path = identifier.as_ref();
+            path.file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .to_string()
+        });
+        core::iter::once(self.root.clone()).chain(names).collect()
+    }
+
+    /// Indices of the segments which fit into the given width.
+    /// A gap between two indices is where the ellipsis goes.
+    fn visible_segments(&self, labels: &[String], width: usize) -> Vec<usize> {
+        let separator_width = self.separator.width();
+        let total = labels.iter().map(|label| label.width()).sum::<usize>()
+            + separator_width * labels.len().saturating_sub(1);
+        if total <= width || labels.len() <= 2 {
+            return (0..labels.len()).collect();
+        }
+
+        // Root, ellipsis and last segment are always shown, fill up from the end
+        let last = labels.len() - 1;
+        let mut used =
+            labels[0].width() + ELLIPSIS.width() + labels[last].width() + separator_width * 2;
+        let mut tail = vec![last];
+        for index in (1..last).rev() {
+            let needed = labels[index].width() + separator_width;
+            if used + needed > width {
+                break;
+            }
+            used += needed;
+            tail.push(index);
+        }
+        tail.reverse();
+
+        let mut indices = vec![0];
+        indices.append(&mut tail);
+        indices
+    }
+}
+
+impl<Identifier> StatefulWidget for Breadcrumb<'_, Identifier>
+where
+    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+{
+    type State = BreadcrumbState<Identifier>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        buf.set_style(area, self.style);
+        state.last_segments.clear();
+        if area.width < 1 || area.height < 1 {
+            return;
+        }
+
+        let labels = self.labels();
+        let indices = self.visible_segments(&labels, area.width as usize);
+        let last = labels.len() - 1;
+
+        let mut x = area.x;
+        let mut previous = None;
+        for index in indices {
+            let remaining = area.right().saturating_sub(x) as usize;
+            if let Some(previous) = previous {
+                let separator = if index > previous + 1 {
+                    format!("{}{ELLIPSIS}{}", self.separator, self.separator)
+                } else {
+                    self.separator.clone()
+                };
+                (x, _) = buf.set_stringn(x, area.y, separator, remaining, self.separator_style);
+            }
+            previous = Some(index);
+
+            let style = if index == last {
+                self.last_style
+            } else {
+                Style::new()
+            };
+            let remaining = area.right().saturating_sub(x) as usize;
+            let start = x;
+            (x, _) = buf.set_stringn(x, area.y, &labels[index], remaining, style);
+            if x > start {
+                let segment_area = Rect::new(start, area.y, x - start, 1);
+                state
+                    .last_segments
+                    .push((segment_area, self.selected[..index].to_vec()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorer::state::ExplorerState;
+
+    const SELECTED: [&str; 4] = [
+        "/r/src",
+        "/r/src/tree",
+        "/r/src/tree/flatten",
+        "/r/src/tree/flatten/depth.rs",
+    ];
+
+    #[track_caller]
+    fn render(width: u16, state: &mut BreadcrumbState<&'static str>) -> Buffer {
+        let breadcrumb = Breadcrumb::new("ki", &SELECTED)
+            .separator("/")
+            .last_style(Style::new());
+        let area = Rect::new(0, 0, width, 1);
+        let mut buffer = Buffer::empty(area);
+        breadcrumb.render(area, &mut buffer, state);
+        buffer
+    }
+
+    #[test]
+    fn everything_fits() {
+        let mut state = BreadcrumbState::default();
+        let buffer = render(30, &mut state);
+        assert_eq!(
+            buffer,
+            Buffer::with_lines(["ki/src/tree/flatten/depth.rs  "])
+        );
+        assert_eq!(state.segment_at(Position::new(0, 0)), Some([].as_slice()));
+        assert_eq!(
+            state.segment_at(Position::new(8, 0)),
+            Some(["/r/src", "/r/src/tree"].as_slice())
+        );
+        assert_eq!(state.segment_at(Position::new(2, 0)), None);
+    }
+
+    #[test]
+    fn elides_middle_segments() {
+        let mut state = BreadcrumbState::default();
+        let buffer = render(22, &mut state);
+        assert_eq!(buffer, Buffer::with_lines(["ki/…/flatten/depth.rs "]));
+        assert_eq!(state.segment_at(Position::new(3, 0)), None);
+        assert_eq!(
+            state.segment_at(Position::new(5, 0)),
+            Some(SELECTED[..3].as_ref())
+        );
+    }
+
+    #[test]
+    fn clicked_segment_collapses_tree() {
+        let mut breadcrumb_state = BreadcrumbState::default();
+        _ = render(30, &mut breadcrumb_state);
+
+        let mut state = ExplorerState::default();
+        state.expand(SELECTED[..1].to_vec());
+        state.expand(SELECTED[..2].to_vec());
+        state.expand(SELECTED[..3].to_vec());
+        state.select(SELECTED.to_vec());
+
+        let clicked = breadcrumb_state.segment_at(Position::new(8, 0)).unwrap();
+        assert!(state.collapse_to(clicked.to_vec()));
+        assert_eq!(state.selected, SELECTED[..2]);
+        assert_eq!(state.expanded.len(), 1);
+
+        let clicked = breadcrumb_state.segment_at(Position::new(0, 0)).unwrap();
+        assert!(state.collapse_to(clicked.to_vec()));
+        assert_eq!(state.selected, SELECTED[..1]);
+        assert!(state.expanded.is_empty());
+    }
+}
@@ -0,0 +1,475 @@
+use core::fmt::{self, Write as _};
+use std::collections::HashMap;
+use std::io::{self, Read as _};
+use std::path::Path;
+
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::fs::FileSystem;
+use crate::preview::highlight::{HighlightTheme, TokenKind};
+use crate::tree::source::{LazyTree, TreeSource};
+
+/// Key TOML uses to pass a datetime through serde.
+const TOML_DATETIME: &str = "$__toml_private_datetime";
+
+/// Formats of structured documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Detect the format by the extension of the path.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// A value of a document, keeping the order of keys as written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    /// The number as written by the parser, like `42` or `1.5`
+    Number(String),
+    String(String),
+    Array(Vec<Self>),
+    /// Entries with unique keys. A repeated key keeps its last value at the place of the first,
+    /// like `serde_json` does
+    Object(Vec<(String, Self)>),
+}
+
+impl Value {
+    /// The value at the given path of segments.
+    #[must_use]
+    pub fn get(&self, identifier: &[Segment]) -> Option<&Self> {
+        identifier
+            .iter()
+            .try_fold(self, |value, segment| match (value, segment) {
+                (Self::Object(entries), Segment::Key(key)) => entries
+                    .iter()
+                    .find(|(other, _)| other == key)
+                    .map(|(_, value)| value),
+                (Self::Array(values), Segment::Index(index)) => values.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Segments to the children of an array or object, empty for scalars.
+    #[must_use]
+    pub fn segments(&self) -> Vec<Segment> {
+        match self {
+            Self::Object(entries) => entries
+                .iter()
+                .map(|(key, _)| Segment::Key(key.clone()))
+                .collect(),
+            Self::Array(values) => (0..values.len()).map(Segment::Index).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Scalars as written, arrays and objects by their amount of children like `[3]` or `{3}`.
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => formatter.write_str("null"),
+            Self::Bool(value) => write!(formatter, "{value}"),
+            Self::Number(number) => formatter.write_str(number),
+            Self::String(string) => write!(formatter, "{string:?}"),
+            Self::Array(values) => write!(formatter, "[{}]", values.len()),
+            Self::Object(entries) => write!(formatter, "{{{}}}", entries.len()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON, TOML or YAML value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.to_string()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.to_string()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::Number(value.to_string()))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries: Vec<(String, Value)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        while let Some((key, value)) = map.next_entry::<Value, Value>()? {
+            // YAML allows keys which are no strings, `1` and `"1"` end up as the same key
+            let key = match key {
+                Value::String(key) => key,
+                key => key.to_string(),
+            };
+            if let Some(&position) = positions.get(&key) {
+                entries[position].1 = value;
+            } else {
+                positions.insert(key.clone(), entries.len());
+                entries.push((key, value));
+            }
+        }
+        // TOML passes datetimes as a map with a single private key
+        if let [(key, Value::String(datetime))] = entries.as_slice() {
+            if key == TOML_DATETIME {
+                return Ok(Value::String(datetime.clone()));
+            }
+        }
+        Ok(Value::Object(entries))
+    }
+}
+
+/// A step from a value to one of its children.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Path expression of the node like `a.b[3].c`, for copying it. Keys which are no plain words
+/// are quoted as JSON strings like `a["two words"]`, as `jq` reads them. The document itself is `.`.
+#[must_use]
+pub fn path_expression(identifier: &[Segment]) -> String {
+    let mut expression = String::new();
+    for segment in identifier {
+        match segment {
+            Segment::Key(key) if is_plain_key(key) => {
+                if !expression.is_empty() {
+                    expression.push('.');
+                }
+                expression.push_str(key);
+            }
+            Segment::Key(key) => {
+                let quoted = serde_json::to_string(key).unwrap_or_default();
+                _ = write!(expression, "[{quoted}]");
+            }
+            Segment::Index(index) => {
+                _ = write!(expression, "[{index}]");
+            }
+        }
+    }
+    if expression.is_empty() {
+        expression.push('.');
+    }
+    expression
+}
+
+fn is_plain_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|char| char.is_alphanumeric() || char == '_')
+}
+
+/// A JSON, TOML or YAML document as a [`TreeSource`]: keys and array indices are the labels,
+/// scalars show their value inline colored by their type.
+///
+/// Browse it with a [`LazyTree`], see [`document_tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub format: Format,
+    pub value: Value,
+    /// Styles of keys ([`TokenKind::Key`]), strings, numbers and other literals
+    pub theme: HighlightTheme,
+}
+
+impl Document {
+    /// Parse the text of a document.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the text is not valid in the given format.
+    pub fn parse(text: &str, format: Format) -> io::Result<Self> {
+        let invalid = |error: &dyn fmt::Display| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{format:?}: {error}"))
+        };
+        let value = match format {
+            Format::Json => serde_json::from_str(text).map_err(|error| invalid(&error))?,
+            Format::Toml => toml::from_str(text).map_err(|error| invalid(&error))?,
+            Format::Yaml => serde_yaml::from_str(text).map_err(|error| invalid(&error))?,
+        };
+        Ok(Self {
+            format,
+            value,
+            theme: HighlightTheme::default(),
+        })
+    }
+
+    /// Read a document from the given [`FileSystem`], its format detected by the extension.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the format is unknown, the file can not be read or is not valid.
+    pub fn load(fs: &dyn FileSystem, path: &Path) -> io::Result<Self> {
+        let format = Format::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} is no JSON, TOML or YAML file", path.display()),
+            )
+        })?;
+        let mut text = String::new();
+        fs.open(path)?.read_to_string(&mut text)?;
+        Self::parse(&text, format)
+    }
+
+    #[must_use]
+    pub const fn theme(mut self, theme: HighlightTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Style of a value by its type.
+    #[must_use]
+    pub const fn value_style(&self, value: &Value) -> Style {
+        match value {
+            Value::String(_) => self.theme.style(TokenKind::String),
+            Value::Number(_) => self.theme.style(TokenKind::Number),
+            Value::Bool(_) | Value::Null => self.theme.style(TokenKind::Literal),
+            Value::Array(_) | Value::Object(_) => Style::new().add_modifier(Modifier::DIM),
+        }
+    }
+}
+
+impl TreeSource for Document {
+    type Identifier = Segment;
+
+    fn roots(&self) -> io::Result<Vec<Segment>> {
+        Ok(self.value.segments())
+    }
+
+    fn children(&self, identifier: &[Segment]) -> io::Result<Vec<Segment>> {
+        self.value
+            .get(identifier)
+            .map(Value::segments)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} is not in the document", path_expression(identifier)),
+                )
+            })
+    }
+
+    fn text(&self, identifier: &[Segment]) -> Text<'static> {
+        let label = match identifier.last() {
+            Some(Segment::Key(key)) => Span::styled(key.clone(), self.theme.style(TokenKind::Key)),
+            Some(Segment::Index(index)) => Span::raw(format!("[{index}]")),
+            None => Span::raw("."),
+        };
+        let Some(value) = self.value.get(identifier) else {
+            return Text::from(label);
+        };
+        let separator = if matches!(value, Value::Array(_) | Value::Object(_)) {
+            " "
+        } else {
+            ": "
+        };
+        Text::from(Line::from(vec![
+            label,
+            Span::raw(separator),
+            Span::styled(value.to_string(), self.value_style(value)),
+        ]))
+    }
+}
+
+/// A [`LazyTree`] of the document, which only builds the items of the visible values.
+///
+/// # Errors
+///
+/// Errors when the tree can not be built.
+pub fn document_tree(document: Document) -> io::Result<LazyTree<Document>> {
+    LazyTree::new(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidgetRef as _;
+
+    use super::*;
+    use crate::explorer::state::ExplorerState;
+    use crate::fs::memory::InMemoryFs;
+
+    const TOML: &str = "name = \"ki\"\nversion = 3\n\n[package]\nauthors = [\"a\", \"b\"]\nreleased = 2024-05-01\n";
+
+    #[test]
+    fn formats_keep_key_order() {
+        let json = Document::parse(r#"{"b": 1, "a": [true, null, 1.5]}"#, Format::Json).unwrap();
+        assert_eq!(
+            json.value,
+            Value::Object(vec![
+                ("b".to_string(), Value::Number("1".to_string())),
+                (
+                    "a".to_string(),
+                    Value::Array(vec![
+                        Value::Bool(true),
+                        Value::Null,
+                        Value::Number("1.5".to_string()),
+                    ])
+                ),
+            ])
+        );
+
+        let yaml = Document::parse("b: 1\na:\n  - true\n  - ~\n  - 1.5\n", Format::Yaml).unwrap();
+        assert_eq!(yaml.value, json.value);
+
+        let toml = Document::parse(TOML, Format::Toml).unwrap();
+        assert_eq!(
+            toml.value.get(&[
+                Segment::Key("package".to_string()),
+                Segment::Key("released".to_string())
+            ]),
+            Some(&Value::String("2024-05-01".to_string()))
+        );
+        assert!(Document::parse("{", Format::Json).is_err());
+    }
+
+    #[test]
+    fn repeated_keys_keep_the_last_value() {
+        let json = Document::parse(r#"{"a": 1, "b": 2, "a": 3}"#, Format::Json).unwrap();
+        assert_eq!(
+            json.value,
+            Value::Object(vec![
+                ("a".to_string(), Value::Number("3".to_string())),
+                ("b".to_string(), Value::Number("2".to_string())),
+            ])
+        );
+
+        let yaml = Document::parse("1: one\n\"1\": two\n", Format::Yaml).unwrap();
+        assert_eq!(
+            yaml.value,
+            Value::Object(vec![("1".to_string(), Value::String("two".to_string()))])
+        );
+        assert!(document_tree(yaml).is_ok());
+    }
+
+    #[test]
+    fn path_expressions() {
+        let identifier = [
+            Segment::Key("a".to_string()),
+            Segment::Key("b".to_string()),
+            Segment::Index(3),
+            Segment::Key("c".to_string()),
+            Segment::Key("two words".to_string()),
+        ];
+        assert_eq!(path_expression(&identifier), r#"a.b[3].c["two words"]"#);
+        assert_eq!(
+            path_expression(&[
+                Segment::Key("foo-bar".to_string()),
+                Segment::Key("\u{1b}\"".to_string()),
+            ]),
+            r#"["foo-bar"]["\u001b\""]"#
+        );
+        assert_eq!(path_expression(&[Segment::Index(0)]), "[0]");
+        assert_eq!(path_expression(&[]), ".");
+    }
+
+    #[test]
+    fn renders_a_document() {
+        let file_system = InMemoryFs::new().with_file("/ki/Cargo.toml", TOML);
+        let document = Document::load(&file_system, Path::new("/ki/Cargo.toml")).unwrap();
+        let theme = document.theme;
+        let mut tree = document_tree(document).unwrap();
+
+        let mut state = ExplorerState::default();
+        state.expand(vec![Segment::Key("package".to_string())]);
+        tree.load(&state).unwrap();
+
+        let area = Rect::new(0, 0, 24, 5);
+        let mut buffer = Buffer::empty(area);
+        tree.tree.render_ref(area, &mut buffer, &mut state);
+        let rows = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                "  name: \"ki\"            ",
+                "  version: 3            ",
+                "▼ package {2}           ",
+                "  ▶ authors [2]         ",
+                "    released: \"2024-05-0",
+            ]
+        );
+        // Values are colored by their type
+        assert_eq!(buffer[(8, 0)].fg, theme.string.fg.unwrap());
+        assert_eq!(buffer[(11, 1)].fg, theme.number.fg.unwrap());
+    }
+}
@@ -1,20 +1,37 @@
+use core::cmp::Ordering;
+use core::fmt::Debug;
 use core::hash::Hash;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Component, Path};
+use std::rc::Rc;
+
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Stylize;
-use ratatui::widgets::{Block, BorderType, Borders, StatefulWidgetRef, Widget};
-use state::ExplorerState;
-use std::fmt::Debug;
-use std::path::Path;
+use ratatui::style::{Style, Stylize as _};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, BorderType, Borders, StatefulWidgetRef, Widget as _};
 
-use crate::fs::PathLike;
+use crate::explorer::error::ExplorerError;
+use crate::explorer::scan::Scan;
+use crate::explorer::state::ExplorerState;
+use crate::explorer::vault::{VaultOptions, is_note};
+use crate::explorer::workspace::WorkspaceRoot;
+use crate::fs::{FileSystem, PathLike, StdFs};
 use crate::tree::{Tree, item::TreeItem};
-use std::collections::BTreeSet;
-use std::io;
 
+pub mod bookmarks;
+pub mod error;
+pub mod history;
+mod persist;
+pub mod scan;
+#[cfg(feature = "serde")]
+pub mod session;
 pub mod state;
+pub mod vault;
+pub mod workspace;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Explorer<'text, T>
 where
     T: AsRef<Path> + Clone + Eq + PartialEq + Ord,
@@ -23,12 +40,48 @@ where
     pub entries: BTreeSet<T>,
     pub root_path: T,
     pub tree: Tree<'text, T>,
+    /// Options of the vault mode, `None` when the entries are shown as plain files
+    pub vault: Option<VaultOptions>,
+    /// Root folders of the workspace mode, see [`add_root`](Self::add_root).
+    /// Empty when only the entries below the [`root_path`](Self::root_path) are shown.
+    pub roots: Vec<WorkspaceRoot<T>>,
+    /// Where the entries live, the local disk by default
+    pub fs: Rc<dyn FileSystem>,
+    /// Entries which can not be shown as a plain file or directory, rendered with an error marker
+    pub errors: BTreeMap<T, ExplorerError>,
+    /// The background scan adding entries, see [`start_scan`](Self::start_scan)
+    pub scan: Option<Rc<Scan>>,
+}
+
+impl<T> PartialEq for Explorer<'_, T>
+where
+    T: AsRef<Path> + Clone + Eq + PartialEq + Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title
+            && self.entries == other.entries
+            && self.root_path == other.root_path
+            && self.tree == other.tree
+            && self.vault == other.vault
+            && self.roots == other.roots
+            && self.errors == other.errors
+            && Rc::ptr_eq(&self.fs, &other.fs)
+            && match (&self.scan, &other.scan) {
+                (Some(scan), Some(other)) => Rc::ptr_eq(scan, other),
+                (scan, other) => scan.is_none() && other.is_none(),
+            }
+    }
 }
 
 impl<'text, T> Explorer<'text, T>
 where
     T: PathLike + Clone + Eq + PartialEq + Ord + Debug,
 {
+    /// Create an empty `Explorer`, populated by [`add_entries`](Self::add_entries).
+    ///
+    /// # Errors
+    ///
+    /// Errors when the empty tree can not be created.
     pub fn new(title: &str, root_path: &'text T) -> io::Result<Self> {
         // Create empty explorer first
         let explorer = Self {
@@ -36,17 +89,44 @@ where
             entries: BTreeSet::new(),
             root_path: root_path.clone(),
             tree: Tree::new(vec![])?, // Start with empty tree
+            vault: None,
+            roots: Vec::new(),
+            fs: Rc::new(StdFs),
+            errors: BTreeMap::new(),
+            scan: None,
         };
 
         // This will be populated when add_entries is called
         Ok(explorer)
     }
 
+    /// Show the entries as a vault of Markdown notes: notes are labeled by their title,
+    /// ignored directories are left out and folders show the amount of notes below them.
+    ///
+    /// Takes effect on the next [`rebuild_tree`](Self::rebuild_tree).
+    #[must_use]
+    pub fn vault_mode(mut self, options: VaultOptions) -> Self {
+        self.vault = Some(options);
+        self
+    }
+
+    /// Browse the given file system instead of the local disk.
+    #[must_use]
+    pub fn file_system(mut self, fs: impl FileSystem + 'static) -> Self {
+        self.fs = Rc::new(fs);
+        self
+    }
+
     // Add a single entry to the entries map
     pub fn add_entry(&mut self, path: T) {
         self.entries.insert(path);
     }
 
+    /// Add the entries and rebuild the tree.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the tree can not be rebuilt, see [`rebuild_tree`](Self::rebuild_tree).
     pub fn add_entries<I>(&mut self, entries: I) -> io::Result<()>
     where
         I: IntoIterator,
@@ -56,37 +136,521 @@ where
         self.rebuild_tree()
     }
 
-    /// Rebuild the tree based on the current entries
+    /// Add the children of a directory from the [`fs`](Self::fs) and rebuild the tree,
+    /// like when it is expanded for the first time.
+    ///
+    /// Returns the amount of entries which were new.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the directory can not be read, it is then kept in the
+    /// [`errors`](Self::errors) and marked in the tree. Errors when the tree can not be rebuilt.
+    pub fn load_dir(&mut self, dir: &Path) -> io::Result<usize> {
+        let node = self
+            .identifier(dir)
+            .and_then(|mut identifier| identifier.pop());
+        let children = match self.fs.read_dir(dir) {
+            Ok(children) => children,
+            Err(error) => {
+                let unreadable = ExplorerError::unreadable(dir, &error);
+                if let Some(node) = node {
+                    self.errors.insert(node, unreadable);
+                    self.rebuild_tree()?;
+                }
+                return Err(error);
+            }
+        };
+        if let Some(node) = &node {
+            self.errors.remove(node);
+        }
+        let before = self.entries.len();
+        for child in children {
+            let Some(child) = self
+                .identifier(&child)
+                .and_then(|mut identifier| identifier.pop())
+            else {
+                continue;
+            };
+            self.entries.insert(child);
+        }
+        self.rebuild_tree()?;
+        Ok(self.entries.len() - before)
+    }
+
+    /// Scan below the [`root_path`](Self::root_path), or the [`roots`](Self::roots) in workspace
+    /// mode, on a background thread. Found entries are added by [`poll_scan`](Self::poll_scan),
+    /// which is meant to be called on every tick. A running scan is cancelled.
+    ///
+    /// The thread needs its own handle of the file system, like [`StdFs`].
+    pub fn start_scan<F>(&mut self, fs: F)
+    where
+        F: FileSystem + Send + 'static,
+    {
+        self.cancel_scan();
+        let roots = if self.roots.is_empty() {
+            vec![self.root_path.as_ref().to_path_buf()]
+        } else {
+            self.roots
+                .iter()
+                .map(|root| root.path.as_ref().to_path_buf())
+                .collect()
+        };
+        self.scan = Some(Rc::new(Scan::spawn(fs, roots)));
+    }
+
+    /// Add the entries the [`scan`](Self::scan) found since the last call to the tree.
+    /// The tree is not rebuilt, the entries are inserted where a rebuild would put them.
+    ///
+    /// Returns the amount of entries which were new.
+    ///
+    /// # Errors
+    ///
+    /// Errors when an entry is already in the tree without being one of the
+    /// [`entries`](Self::entries).
+    pub fn poll_scan(&mut self) -> io::Result<usize> {
+        let Some(scan) = self.scan.clone() else {
+            return Ok(0);
+        };
+        let mut added = 0;
+        // Directories whose label changes, by a note below them or an error
+        let mut relabeled = BTreeSet::new();
+        for entry in scan.receive() {
+            let Some(path) = self
+                .identifier(&entry.path)
+                .and_then(|mut identifier| identifier.pop())
+            else {
+                continue;
+            };
+            if !self.entries.insert(path.clone()) {
+                continue;
+            }
+            added += 1;
+            let is_dir = entry
+                .is_dir
+                .unwrap_or_else(|| inspect(&*self.fs, &path, &mut self.errors));
+            let counted = self.vault.is_some() && !is_dir && is_note(path.as_ref());
+            for (identifier, top_level) in self.tree_identifiers(&path) {
+                let item = self.scanned_item(&path, is_dir);
+                insert_item(self.tree.items_mut(), &identifier, item, Some(top_level))?;
+                if counted {
+                    relabeled.extend(
+                        (top_level + 1..identifier.len()).map(|len| identifier[..len].to_vec()),
+                    );
+                }
+            }
+        }
+        for error in scan.take_errors() {
+            let Some(node) = self
+                .identifier(&error.path)
+                .and_then(|mut identifier| identifier.pop())
+            else {
+                continue;
+            };
+            relabeled.extend(
+                self.tree_identifiers(&node)
+                    .into_iter()
+                    .map(|(identifier, _)| identifier),
+            );
+            self.errors
+                .insert(node, ExplorerError::unreadable(&error.path, &error.error));
+        }
+        for identifier in relabeled {
+            if let Some(item) = find_item(self.tree.items_mut(), &identifier) {
+                relabel_directory(item, self.vault.as_ref(), &self.errors);
+            }
+        }
+        Ok(added)
+    }
+
+    /// Identifiers of the nodes showing the entry in the tree, with the amount of workspace
+    /// headers above the top level. Empty when the entry is left out of the tree.
+    fn tree_identifiers(&self, path: &T) -> Vec<(Vec<T>, usize)> {
+        let ignored = |root: &T| {
+            self.vault
+                .as_ref()
+                .is_some_and(|vault| vault.is_ignored(root.as_ref(), path.as_ref()))
+        };
+        if self.roots.is_empty() {
+            return chain(&self.root_path, path.as_ref(), Vec::new())
+                .filter(|_| !ignored(&self.root_path))
+                .map(|identifier| (identifier, 0))
+                .into_iter()
+                .collect();
+        }
+        // An entry of nested roots is shown below each of them
+        self.roots
+            .iter()
+            .filter(|root| root.contains(path.as_ref()) && !root.is_ignored(path.as_ref()))
+            .filter(|root| !ignored(&root.path))
+            .filter_map(|root| chain(&root.path, path.as_ref(), vec![root.path.clone()]))
+            .filter(|identifier| identifier.len() > 1)
+            .map(|identifier| (identifier, 1))
+            .collect()
+    }
+
+    /// Node of an entry found by a scan, without children yet.
+    fn scanned_item(&self, path: &T, is_dir: bool) -> TreeItem<'text, T> {
+        let file_name = path.as_ref().file_name().map_or_else(
+            || path.as_ref().display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        let label = if is_dir {
+            match &self.vault {
+                Some(vault) => Text::from(vault.directory_label(&file_name, 0)),
+                None => Text::raw(file_name),
+            }
+        } else {
+            Text::raw(leaf_label(
+                &*self.fs,
+                path.as_ref(),
+                &file_name,
+                self.vault.as_ref(),
+            ))
+        };
+        TreeItem::new_leaf(path.clone(), node_text(label, path, &self.errors))
+    }
+
+    /// Stop the [`scan`](Self::scan), the entries found so far are kept.
+    pub fn cancel_scan(&mut self) {
+        if let Some(scan) = self.scan.take() {
+            scan.cancel();
+        }
+    }
+
+    /// A [`scan`](Self::scan) is running, the tree shows partial results.
+    #[must_use]
+    pub fn is_scanning(&self) -> bool {
+        self.scan.as_ref().is_some_and(|scan| !scan.is_finished())
+    }
+
+    /// Rebuild the tree based on the current entries.
+    ///
+    /// Entries which vanished, broken symbolic links and other entries whose kind can not be read
+    /// are kept in the [`errors`](Self::errors) and rendered with an error marker.
+    ///
+    /// # Errors
+    ///
+    /// Errors when identifiers are duplicated.
     pub fn rebuild_tree(&mut self) -> io::Result<()> {
-        // Build tree starting from root path, but don't show root as an item
-        let children = self
-            .entries
+        // Directories stay unreadable until loaded again, the rest is found again below
+        let mut errors = core::mem::take(&mut self.errors);
+        errors.retain(|path, error| {
+            matches!(error, ExplorerError::Unreadable { .. }) && self.entries.contains(path)
+        });
+        let result = self.build_items(&mut errors);
+        self.errors = errors;
+        self.tree.set_items(result?)
+    }
+
+    fn build_items(
+        &self,
+        errors: &mut BTreeMap<T, ExplorerError>,
+    ) -> io::Result<Vec<TreeItem<'text, T>>> {
+        let vault = self.vault.as_ref();
+        if self.roots.is_empty() {
+            return top_level_items(&*self.fs, &self.root_path, &self.entries, vault, errors);
+        }
+
+        self.roots
             .iter()
-            .filter(|p| p.as_ref().parent() == Some(self.root_path.as_ref()))
-            .map(|path| {
-                if path.is_dir() {
-                    build_directory_tree(&self.root_path, path, &self.entries)
-                } else {
-                    Ok(TreeItem::new_leaf(
-                        path.clone(),
-                        path.as_ref()
-                            .file_name()
-                            .ok_or_else(|| {
-                                io::Error::new(io::ErrorKind::InvalidData, "Path has no file name")
-                            })?
-                            .to_string_lossy()
-                            .to_string(),
-                    ))
-                }
+            .map(|root| {
+                let entries = self
+                    .entries
+                    .iter()
+                    .filter(|path| root.contains(path.as_ref()) && !root.is_ignored(path.as_ref()))
+                    .cloned()
+                    .collect();
+                let children = top_level_items(&*self.fs, &root.path, &entries, vault, errors)?;
+                TreeItem::new(root.path.clone(), root.header(), children)
             })
-            .collect::<io::Result<Vec<_>>>()?;
+            .collect()
+    }
+
+    /// Make the given directory the root and rebuild the tree.
+    /// The selected and expanded nodes of the state are translated to the new root,
+    /// nodes outside of it are dropped. The [`history`](ExplorerState::history) is cleared.
+    ///
+    /// Entries outside of the new root are kept but not shown, so going back keeps them.
+    /// Add the entries of the new root with [`add_entries`](Self::add_entries).
+    /// The [`roots`](Self::roots) of the workspace mode are not affected.
+    /// A running [`scan`](Self::scan) is cancelled.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the tree can not be rebuilt, see [`rebuild_tree`](Self::rebuild_tree).
+    pub fn set_root(&mut self, state: &mut ExplorerState<T>, root: T) -> io::Result<()> {
+        self.cancel_scan();
+        self.root_path = root;
+
+        let translated = |identifier: &Vec<T>| {
+            identifier
+                .last()
+                .and_then(|path| self.identifier(path.as_ref()))
+        };
+        let selected = translated(&state.selected).unwrap_or_default();
+        state.select(selected);
+        state.expanded = state.expanded.iter().filter_map(translated).collect();
+        state.history.clear();
+        state.offset = 0;
+        state.horizontal_offset = 0;
+
+        self.rebuild_tree()
+    }
+
+    /// Make the selected directory the root, like `cd` into it.
+    ///
+    /// Returns `false` when the selected node is not a directory.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the tree can not be rebuilt, see [`set_root`](Self::set_root).
+    pub fn enter_selected(&mut self, state: &mut ExplorerState<T>) -> io::Result<bool> {
+        let Some(directory) = state
+            .selected
+            .last()
+            .filter(|path| self.fs.is_dir(path.as_ref()))
+            .cloned()
+        else {
+            return Ok(false);
+        };
+        self.set_root(state, directory)?;
+        Ok(true)
+    }
+
+    /// Make the parent directory of the root the root, like `cd ..`.
+    /// The previous root is added to the [`entries`](Self::entries) so it is shown,
+    /// it is expanded, and selected when nothing below it was.
+    ///
+    /// Returns `false` when the root has no parent.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the tree can not be rebuilt, see [`set_root`](Self::set_root).
+    pub fn go_up(&mut self, state: &mut ExplorerState<T>) -> io::Result<bool> {
+        let previous = self.root_path.clone();
+        let parent =
+            PathLike::parent(&previous).filter(|parent| !parent.as_ref().as_os_str().is_empty());
+        let Some(parent) = parent else {
+            return Ok(false);
+        };
+        self.entries.insert(previous.clone());
+        self.set_root(state, parent)?;
+        if state.selected.is_empty() {
+            state.select(vec![previous.clone()]);
+        }
+        state.expand(vec![previous]);
+        Ok(true)
+    }
+
+    /// Add a root folder and rebuild the tree. With roots the explorer is in workspace mode:
+    /// each root is a header above the [`entries`](Self::entries) below it,
+    /// the [`root_path`](Self::root_path) is no longer shown.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the tree can not be rebuilt, see [`rebuild_tree`](Self::rebuild_tree).
+    pub fn add_root(&mut self, root: WorkspaceRoot<T>) -> io::Result<()> {
+        self.roots.push(root);
+        self.rebuild_tree()
+    }
+
+    /// Remove the root folder at the given path together with its entries and rebuild the tree.
+    ///
+    /// Returns `false` when there is no such root.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the tree can not be rebuilt, see [`rebuild_tree`](Self::rebuild_tree).
+    pub fn remove_root(&mut self, path: &Path) -> io::Result<bool> {
+        let Some(index) = self
+            .roots
+            .iter()
+            .position(|root| root.path.as_ref() == path)
+        else {
+            return Ok(false);
+        };
+        let root = self.roots.remove(index);
+        // Keep the entries of nested roots
+        self.entries.retain(|entry| {
+            !root.contains(entry.as_ref())
+                || self
+                    .roots
+                    .iter()
+                    .any(|other| other.contains(entry.as_ref()))
+        });
+        self.rebuild_tree()?;
+        Ok(true)
+    }
+
+    /// The innermost root folder of the workspace mode the path lies below.
+    #[must_use]
+    pub fn root_of(&self, path: &Path) -> Option<&WorkspaceRoot<T>> {
+        self.roots
+            .iter()
+            .filter(|root| root.contains(path))
+            .max_by_key(|root| root.path.as_ref().components().count())
+    }
+
+    /// Whether the path is one of the [`entries`](Self::entries) or a root of the workspace mode.
+    #[must_use]
+    pub fn contains(&self, path: &T) -> bool {
+        self.entries.contains(path) || self.roots.iter().any(|root| &root.path == path)
+    }
 
-        self.tree = Tree::new(children)?;
+    /// Identifier of the node at the given path, absolute or relative to the root:
+    /// the chain of paths from the top level node down to it.
+    /// In workspace mode the chain starts with the root the path lies below,
+    /// relative paths are relative to the first root.
+    ///
+    /// Returns `None` when the path is not below the root.
+    #[must_use]
+    pub fn identifier(&self, path: &Path) -> Option<Vec<T>> {
+        if self.roots.is_empty() {
+            return chain(&self.root_path, path, Vec::new());
+        }
+        let root = self
+            .root_of(path)
+            .or_else(|| self.roots.first().filter(|_| path.is_relative()))?;
+        chain(&root.path, path, vec![root.path.clone()])
+    }
+
+    /// Jump to the node at the given path: expand its ancestors, select it
+    /// and scroll it into view on next render. The jump is recorded in the history.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path is not below the root or not among the [`entries`](Self::entries).
+    pub fn reveal(&self, state: &mut ExplorerState<T>, path: &Path) -> io::Result<()> {
+        let identifier = self.identifier(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is not below {}",
+                    path.display(),
+                    self.root_path.as_ref().display()
+                ),
+            )
+        })?;
+        if let Some(missing) = identifier.iter().find(|path| !self.contains(path)) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not an entry", missing.as_ref().display()),
+            ));
+        }
+
+        for depth in 1..identifier.len() {
+            state.expand(identifier[..depth].to_vec());
+        }
+        state.jump(identifier);
+        state.scroll_selected_into_view();
         Ok(())
     }
+
+    /// Reveal the node the bookmark with the given label points to, see [`reveal`](Self::reveal).
+    ///
+    /// # Errors
+    ///
+    /// Errors when there is no such bookmark or its node can not be revealed.
+    pub fn jump_to_bookmark(&self, state: &mut ExplorerState<T>, label: &str) -> io::Result<()> {
+        let path = state
+            .bookmarks
+            .get(label)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("No bookmark {label:?}"))
+            })?
+            .to_path_buf();
+        self.reveal(state, &path)
+    }
+
+    /// Rename or move the entry at `from` to `to` on disk, paths absolute or relative to the root.
+    /// The entries below it, their [`errors`](Self::errors), the selection, the expanded nodes
+    /// and the bookmarks of the state follow it, then the tree is rebuilt.
+    ///
+    /// # Errors
+    ///
+    /// Errors when a path is not below the root, `from` is not an entry, `to` already exists
+    /// or the rename fails.
+    pub fn rename(
+        &mut self,
+        state: &mut ExplorerState<T>,
+        from: &Path,
+        to: &Path,
+    ) -> io::Result<()> {
+        let outside = |path: &Path| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is not below {}",
+                    path.display(),
+                    self.root_path.as_ref().display()
+                ),
+            )
+        };
+        let from = self
+            .identifier(from)
+            .and_then(|mut identifier| identifier.pop())
+            .ok_or_else(|| outside(from))?;
+        let to = self
+            .identifier(to)
+            .and_then(|mut identifier| identifier.pop())
+            .ok_or_else(|| outside(to))?;
+        if !self.entries.contains(&from) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not an entry", from.as_ref().display()),
+            ));
+        }
+        if self.fs.exists(to.as_ref()) || self.entries.contains(&to) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", to.as_ref().display()),
+            ));
+        }
+        self.fs.rename(from.as_ref(), to.as_ref())?;
+
+        let moved = |path: &T| {
+            let relative = path.as_ref().strip_prefix(from.as_ref()).ok()?;
+            Some(if relative.as_os_str().is_empty() {
+                to.clone()
+            } else {
+                to.join(relative)
+            })
+        };
+        self.entries = core::mem::take(&mut self.entries)
+            .into_iter()
+            .map(|path| moved(&path).unwrap_or(path))
+            .collect();
+        self.errors = core::mem::take(&mut self.errors)
+            .into_iter()
+            .map(|(path, mut error)| match moved(&path) {
+                Some(path) => {
+                    error.set_path(path.as_ref().to_path_buf());
+                    (path, error)
+                }
+                None => (path, error),
+            })
+            .collect();
+
+        let moved_identifier = |identifier: &Vec<T>| {
+            identifier
+                .last()
+                .and_then(moved)
+                .and_then(|path| self.identifier(path.as_ref()))
+        };
+        if let Some(selected) = moved_identifier(&state.selected) {
+            state.select(selected);
+        }
+        state.expanded = core::mem::take(&mut state.expanded)
+            .into_iter()
+            .map(|identifier| moved_identifier(&identifier).unwrap_or(identifier))
+            .collect();
+        state.bookmarks.rename_path(from.as_ref(), to.as_ref());
+
+        self.rebuild_tree()
+    }
 }
 
-impl<'text, T> StatefulWidgetRef for Explorer<'text, T>
+impl<T> StatefulWidgetRef for Explorer<'_, T>
 where
     T: AsRef<Path> + Clone + Eq + PartialEq + Ord + Hash + Debug,
 {
@@ -97,11 +661,15 @@ where
         state.last_area = area;
 
         let title = format!(" {} ", self.title);
-        let block = Block::default()
+        let mut block = Block::default()
             .title(title)
             .italic()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded);
+        if let Some(scan) = self.scan.as_ref().filter(|scan| !scan.is_finished()) {
+            let scanning = format!(" scanning… {} ", scan.progress().entries_found);
+            block = block.title(Line::from(scanning).right_aligned());
+        }
 
         let inner_area = block.inner(area);
         block.render(area, buf);
@@ -111,11 +679,52 @@ where
     }
 }
 
+/// Items of the entries directly below the root, without the root itself.
+fn top_level_items<'a, T>(
+    fs: &dyn FileSystem,
+    root_path: &T,
+    entries: &BTreeSet<T>,
+    vault: Option<&VaultOptions>,
+    errors: &mut BTreeMap<T, ExplorerError>,
+) -> io::Result<Vec<TreeItem<'a, T>>>
+where
+    T: PathLike,
+{
+    entries
+        .iter()
+        .filter(|path| path.as_ref().parent() == Some(root_path.as_ref()))
+        .filter(|path| {
+            !vault.is_some_and(|vault| vault.is_ignored(root_path.as_ref(), path.as_ref()))
+        })
+        .map(|path| {
+            let Some(file_name) = path.as_ref().file_name() else {
+                let error = ExplorerError::NoFileName(path.as_ref().to_path_buf());
+                let text = with_error(Text::raw(path.as_ref().display().to_string()), &error);
+                errors.insert(path.clone(), error);
+                return Ok(TreeItem::new_leaf(path.clone(), text));
+            };
+            let file_name = file_name.to_string_lossy();
+            if inspect(fs, path, errors) {
+                build_directory_tree(fs, root_path, path, entries, vault, errors)
+            } else {
+                let label = leaf_label(fs, path.as_ref(), &file_name, vault);
+                Ok(TreeItem::new_leaf(
+                    path.clone(),
+                    node_text(label, path, errors),
+                ))
+            }
+        })
+        .collect()
+}
+
 // Helper function to build a directory tree from paths
 fn build_directory_tree<'a, T>(
+    fs: &dyn FileSystem,
     root_path: &T,
     current_path: &T,
     entries: &BTreeSet<T>,
+    vault: Option<&VaultOptions>,
+    errors: &mut BTreeMap<T, ExplorerError>,
 ) -> io::Result<TreeItem<'a, T>>
 where
     T: PathLike,
@@ -123,6 +732,9 @@ where
     let mut children = Vec::new();
 
     for path in entries {
+        if vault.is_some_and(|vault| vault.is_ignored(root_path.as_ref(), path.as_ref())) {
+            continue;
+        }
         if let Ok(rel_path) = path.as_ref().strip_prefix(current_path.as_ref()) {
             let components: Vec<_> = rel_path.components().collect();
 
@@ -130,29 +742,23 @@ where
                 let component = components[0].as_os_str().to_string_lossy();
                 let full_path = current_path.join(component.as_ref());
 
-                if path.is_dir() {
-                    let child = build_directory_tree(root_path, &full_path, entries)?;
+                if inspect(fs, path, errors) {
+                    let child =
+                        build_directory_tree(fs, root_path, &full_path, entries, vault, errors)?;
                     children.push(child);
                 } else {
-                    children.push(TreeItem::new_leaf(full_path.clone(), component.to_string()));
+                    let label = leaf_label(fs, full_path.as_ref(), &component, vault);
+                    let text = node_text(label, &full_path, errors);
+                    children.push(TreeItem::new_leaf(full_path.clone(), text));
                 }
             }
         }
     }
 
-    // Sort children (directories first, then files)
-    children.sort_by(|a, b| {
-        let a_is_dir = !a.children().is_empty();
-        let b_is_dir = !b.children().is_empty();
-        match (a_is_dir, b_is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.identifier().cmp(b.identifier()),
-        }
-    });
+    children.sort_by(directories_first);
 
     let display_name = if current_path.as_ref() == root_path.as_ref() {
-        "".to_string()
+        String::new()
     } else {
         current_path
             .as_ref()
@@ -162,5 +768,503 @@ where
             .to_string()
     };
 
-    TreeItem::new(current_path.clone(), display_name, children)
+    let text = match vault {
+        Some(vault) => {
+            let notes = entries
+                .iter()
+                .filter(|path| path.as_ref().starts_with(current_path.as_ref()))
+                .filter(|path| is_note(path.as_ref()) && !fs.is_dir(path.as_ref()))
+                .filter(|path| !vault.is_ignored(root_path.as_ref(), path.as_ref()))
+                .count();
+            Text::from(vault.directory_label(&display_name, notes))
+        }
+        None => Text::raw(display_name),
+    };
+
+    let text = node_text(text, current_path, errors);
+    TreeItem::new(current_path.clone(), text, children)
+}
+
+/// Order of the children of a directory: directories first, then files.
+fn directories_first<T>(left: &TreeItem<'_, T>, right: &TreeItem<'_, T>) -> Ordering
+where
+    T: Ord,
+{
+    let left_is_dir = !left.children.is_empty();
+    let right_is_dir = !right.children.is_empty();
+    right_is_dir
+        .cmp(&left_is_dir)
+        .then_with(|| left.identifier.cmp(&right.identifier))
+}
+
+/// Insert the item at the end of the identifier, in the order a rebuild of the tree would
+/// give it: the top level of the entries by path, below it [`directories_first`].
+/// `headers` is the amount of workspace header levels above the top level, `None` below it.
+/// Nothing is inserted when an ancestor is not in the tree.
+fn insert_item<'text, T>(
+    items: &mut Vec<TreeItem<'text, T>>,
+    identifier: &[T],
+    item: TreeItem<'text, T>,
+    headers: Option<usize>,
+) -> io::Result<()>
+where
+    T: Ord,
+{
+    let Some((first, rest)) = identifier.split_first() else {
+        return Ok(());
+    };
+    if rest.is_empty() {
+        if items
+            .iter()
+            .any(|existing| existing.identifier == item.identifier)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "identifier already exists in the tree",
+            ));
+        }
+        insert_sorted(items, item, headers.is_some());
+        return Ok(());
+    }
+    let Some(index) = items.iter().position(|parent| &parent.identifier == first) else {
+        return Ok(());
+    };
+    let was_leaf = items[index].children.is_empty();
+    let below = headers.and_then(|headers| headers.checked_sub(1));
+    insert_item(&mut items[index].children, rest, item, below)?;
+    // A directory which got its first child moves in front of the files
+    if was_leaf && headers.is_none() && !items[index].children.is_empty() {
+        let parent = items.remove(index);
+        insert_sorted(items, parent, false);
+    }
+    Ok(())
+}
+
+fn insert_sorted<'text, T>(
+    items: &mut Vec<TreeItem<'text, T>>,
+    item: TreeItem<'text, T>,
+    top_level: bool,
+) where
+    T: Ord,
+{
+    let position = items.partition_point(|existing| {
+        if top_level {
+            existing.identifier < item.identifier
+        } else {
+            directories_first(existing, &item) == Ordering::Less
+        }
+    });
+    items.insert(position, item);
+}
+
+/// The item at the end of the identifier.
+fn find_item<'items, 'text, T>(
+    items: &'items mut [TreeItem<'text, T>],
+    identifier: &[T],
+) -> Option<&'items mut TreeItem<'text, T>>
+where
+    T: Ord,
+{
+    let (first, rest) = identifier.split_first()?;
+    let item = items.iter_mut().find(|item| &item.identifier == first)?;
+    if rest.is_empty() {
+        Some(item)
+    } else {
+        find_item(&mut item.children, rest)
+    }
+}
+
+/// Label a directory again after notes were added below it or it got an error.
+fn relabel_directory<T>(
+    item: &mut TreeItem<'_, T>,
+    vault: Option<&VaultOptions>,
+    errors: &BTreeMap<T, ExplorerError>,
+) where
+    T: PathLike,
+{
+    let name = item
+        .identifier
+        .as_ref()
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let text = match vault {
+        Some(vault) => Text::from(vault.directory_label(&name, count_notes(&item.children))),
+        None => Text::raw(name),
+    };
+    item.text = node_text(text, &item.identifier, errors);
+}
+
+/// Amount of notes below the items. Items without children are taken as files.
+fn count_notes<T>(items: &[TreeItem<'_, T>]) -> usize
+where
+    T: AsRef<Path>,
+{
+    items
+        .iter()
+        .map(|item| {
+            if item.children.is_empty() {
+                usize::from(is_note(item.identifier.as_ref()))
+            } else {
+                count_notes(&item.children)
+            }
+        })
+        .sum()
+}
+
+/// Append the nodes from the root down to the path to the identifier.
+/// Returns `None` when nothing was appended or the path is not below the root.
+fn chain<T>(root: &T, path: &Path, mut identifier: Vec<T>) -> Option<Vec<T>>
+where
+    T: PathLike,
+{
+    let relative = path.strip_prefix(root.as_ref()).unwrap_or(path);
+    let mut current = root.clone();
+    for component in relative.components() {
+        let Component::Normal(name) = component else {
+            return None;
+        };
+        current = current.join(name);
+        identifier.push(current.clone());
+    }
+    (!identifier.is_empty()).then_some(identifier)
+}
+
+/// Whether the entry is a directory. When its kind can not be read the error is kept
+/// and it is shown as a file.
+fn inspect<T>(fs: &dyn FileSystem, path: &T, errors: &mut BTreeMap<T, ExplorerError>) -> bool
+where
+    T: PathLike,
+{
+    match fs.metadata(path.as_ref()) {
+        Ok(metadata) => metadata.is_dir,
+        Err(error) => {
+            let error = ExplorerError::metadata(fs, path.as_ref(), &error);
+            errors.insert(path.clone(), error);
+            false
+        }
+    }
+}
+
+/// The label with the error of the node when it has one.
+fn node_text<'a, T>(
+    label: impl Into<Text<'a>>,
+    path: &T,
+    errors: &BTreeMap<T, ExplorerError>,
+) -> Text<'a>
+where
+    T: Ord,
+{
+    let text = label.into();
+    match errors.get(path) {
+        Some(error) => with_error(text, error),
+        None => text,
+    }
+}
+
+/// Append the error marker and message to the last line of the text.
+fn with_error<'a>(mut text: Text<'a>, error: &ExplorerError) -> Text<'a> {
+    let marker = Span::styled(format!(" ⚠ {}", error.message()), Style::new().red());
+    match text.lines.last_mut() {
+        Some(line) => line.spans.push(marker),
+        None => text.lines.push(Line::from(marker)),
+    }
+    text
+}
+
+/// Label of a file, the title of a note in vault mode.
+fn leaf_label(
+    fs: &dyn FileSystem,
+    path: &Path,
+    file_name: &str,
+    vault: Option<&VaultOptions>,
+) -> String {
+    vault.map_or_else(
+        || file_name.to_string(),
+        |vault| vault.label(fs, path, file_name),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::fs::memory::InMemoryFs;
+
+    #[test]
+    fn reveal() {
+        let root = PathBuf::from("/project");
+        let mut explorer = Explorer::new("reveal", &root)
+            .unwrap()
+            .file_system(InMemoryFs::new().with_file("/project/a/b/c.rs", ""));
+        explorer
+            .add_entries([root.join("a"), root.join("a/b"), root.join("a/b/c.rs")])
+            .unwrap();
+        assert!(explorer.errors.is_empty());
+        assert_eq!(explorer.tree.items()[0].children().len(), 1);
+
+        let mut state = ExplorerState::default();
+        explorer.reveal(&mut state, &root.join("a/b/c.rs")).unwrap();
+        assert_eq!(
+            state.selected,
+            [root.join("a"), root.join("a/b"), root.join("a/b/c.rs")]
+        );
+        assert!(state.expanded.contains(&vec![root.join("a")]));
+        assert!(
+            state
+                .expanded
+                .contains(&vec![root.join("a"), root.join("a/b")])
+        );
+        assert_eq!(state.expanded.len(), 2);
+        assert!(state.ensure_selected_in_view_on_next_render);
+
+        let outside = explorer.reveal(&mut state, Path::new("/elsewhere/c.rs"));
+        assert_eq!(outside.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let missing = explorer.reveal(&mut state, &root.join("a/missing.rs"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn rename_keeps_state_and_bookmarks() {
+        let root = PathBuf::from("/project");
+        let file_system = InMemoryFs::new().with_file("/project/a/b/c.rs", "");
+
+        let mut explorer = Explorer::new("rename", &root)
+            .unwrap()
+            .file_system(file_system);
+        explorer
+            .add_entries([root.join("a"), root.join("a/b"), root.join("a/b/c.rs")])
+            .unwrap();
+        let mut state = ExplorerState::default();
+        explorer.reveal(&mut state, Path::new("a/b/c.rs")).unwrap();
+        state.mark("c").unwrap();
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        explorer.errors.insert(
+            root.join("a/b"),
+            ExplorerError::unreadable(&root.join("a/b"), &denied),
+        );
+
+        explorer
+            .rename(&mut state, Path::new("a"), Path::new("z"))
+            .unwrap();
+        assert!(explorer.fs.exists(&root.join("z/b/c.rs")));
+        assert!(explorer.entries.contains(&root.join("z/b")));
+        assert!(!explorer.entries.contains(&root.join("a")));
+        assert_eq!(explorer.errors.len(), 1);
+        assert_eq!(
+            explorer
+                .errors
+                .get(&root.join("z/b"))
+                .map(ExplorerError::path),
+            Some(root.join("z/b").as_path())
+        );
+        assert_eq!(
+            state.selected,
+            [root.join("z"), root.join("z/b"), root.join("z/b/c.rs")]
+        );
+        assert!(state.expanded.contains(&vec![root.join("z")]));
+        assert_eq!(
+            state.bookmarks.get("c"),
+            Some(root.join("z/b/c.rs").as_path())
+        );
+
+        state.select(Vec::new());
+        explorer.jump_to_bookmark(&mut state, "c").unwrap();
+        assert_eq!(state.selected.last(), Some(&root.join("z/b/c.rs")));
+        let unknown = explorer.jump_to_bookmark(&mut state, "x");
+        assert_eq!(unknown.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let taken = explorer.rename(&mut state, Path::new("z/b/c.rs"), Path::new("z/b"));
+        assert_eq!(taken.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn change_root() {
+        let root = PathBuf::from("/ki-root");
+        let mut explorer = Explorer::new("root", &root).unwrap();
+        explorer
+            .add_entries([
+                root.join("a"),
+                root.join("a/b"),
+                root.join("a/b/c.rs"),
+                root.join("d.rs"),
+            ])
+            .unwrap();
+        let mut state = ExplorerState::default();
+        explorer.reveal(&mut state, Path::new("a/b/c.rs")).unwrap();
+
+        explorer.set_root(&mut state, root.join("a")).unwrap();
+        assert_eq!(state.selected, [root.join("a/b"), root.join("a/b/c.rs")]);
+        assert_eq!(
+            state.expanded,
+            core::iter::once(vec![root.join("a/b")]).collect()
+        );
+        assert!(state.history.back_entries().is_empty());
+        assert_eq!(explorer.tree.items().len(), 1);
+
+        assert!(explorer.go_up(&mut state).unwrap());
+        assert_eq!(explorer.root_path, root);
+        assert_eq!(
+            state.selected,
+            [root.join("a"), root.join("a/b"), root.join("a/b/c.rs")]
+        );
+        assert!(state.expanded.contains(&vec![root.join("a")]));
+        assert_eq!(explorer.tree.items().len(), 2);
+
+        // Nothing of the previous root is left selected, so the previous root is
+        explorer.set_root(&mut state, root.join("a/b")).unwrap();
+        state.select(Vec::new());
+        explorer.go_up(&mut state).unwrap();
+        assert_eq!(state.selected, [root.join("a/b")]);
+    }
+
+    #[test]
+    fn in_memory_file_system() {
+        let root = PathBuf::from("/project");
+        let file_system = InMemoryFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/README.md", "# Project");
+        let mut explorer = Explorer::new("memory", &root)
+            .unwrap()
+            .file_system(file_system);
+
+        assert_eq!(explorer.load_dir(&root).unwrap(), 2);
+        assert_eq!(explorer.load_dir(&root.join("src")).unwrap(), 1);
+        assert_eq!(explorer.load_dir(&root).unwrap(), 0);
+        let items = explorer.tree.items();
+        assert_eq!(items[1].identifier(), &root.join("src"));
+        assert_eq!(items[1].children().len(), 1);
+
+        let mut state = ExplorerState::default();
+        explorer
+            .rename(&mut state, Path::new("src"), Path::new("lib"))
+            .unwrap();
+        assert!(explorer.fs.exists(&root.join("lib/main.rs")));
+        assert!(explorer.entries.contains(&root.join("lib/main.rs")));
+    }
+
+    #[test]
+    fn background_scan() {
+        let root = PathBuf::from("/project");
+        let file_system = InMemoryFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/README.md", "# Project");
+        let mut explorer = Explorer::new("scan", &root)
+            .unwrap()
+            .file_system(file_system.clone());
+
+        explorer.start_scan(file_system.clone());
+        let deadline = std::time::Instant::now() + core::time::Duration::from_secs(5);
+        let mut added = 0;
+        while explorer.is_scanning() {
+            assert!(std::time::Instant::now() < deadline, "Scan did not finish");
+            added += explorer.poll_scan().unwrap();
+        }
+        assert_eq!(added, 3);
+        assert_eq!(explorer.tree.items().len(), 2);
+        assert_eq!(explorer.scan.as_ref().unwrap().progress().dirs_visited, 2);
+
+        explorer.start_scan(file_system);
+        let scan = Rc::clone(explorer.scan.as_ref().unwrap());
+        let mut state = ExplorerState::default();
+        explorer.set_root(&mut state, root.join("src")).unwrap();
+        assert!(scan.is_cancelled());
+        assert!(!explorer.is_scanning());
+    }
+
+    #[test]
+    fn scan_inserts_like_rebuild() {
+        let root = PathBuf::from("/vault");
+        let file_system = InMemoryFs::new()
+            .with_file("/vault/b.md", "# Bee")
+            .with_file("/vault/a/x.md", "")
+            .with_file("/vault/a/deep/y.md", "# Why")
+            .with_file("/vault/a/z.txt", "")
+            .with_file("/vault/.obsidian/app.json", "{}")
+            .with_file("/vault/c.txt", "")
+            .with_dir("/vault/empty");
+        for vault in [None, Some(VaultOptions::default())] {
+            let mut explorer = Explorer::new("scan", &root)
+                .unwrap()
+                .file_system(file_system.clone());
+            explorer.vault = vault;
+            explorer.start_scan(file_system.clone());
+            let deadline = std::time::Instant::now() + core::time::Duration::from_secs(5);
+            while explorer.is_scanning() {
+                assert!(std::time::Instant::now() < deadline, "Scan did not finish");
+                explorer.poll_scan().unwrap();
+            }
+
+            let scanned = explorer.tree.items().clone();
+            explorer.rebuild_tree().unwrap();
+            assert_eq!(&scanned, explorer.tree.items());
+        }
+    }
+
+    #[test]
+    fn errors_are_marked() {
+        let root = PathBuf::from("/project");
+        let file_system = InMemoryFs::new()
+            .with_file("/project/README.md", "# Project")
+            .with_dir("/project/src");
+        let mut explorer = Explorer::new("errors", &root)
+            .unwrap()
+            .file_system(file_system);
+        explorer
+            .add_entries([
+                root.join("README.md"),
+                root.join("src"),
+                root.join("gone.rs"),
+                root.join(".."),
+            ])
+            .unwrap();
+        assert_eq!(explorer.tree.items().len(), 4);
+        assert_eq!(
+            explorer.errors.get(&root.join("gone.rs")),
+            Some(&ExplorerError::Vanished(root.join("gone.rs")))
+        );
+        assert_eq!(
+            explorer.errors.get(&root.join("..")),
+            Some(&ExplorerError::NoFileName(root.join("..")))
+        );
+        let items = explorer.tree.items();
+        let gone = items
+            .iter()
+            .find(|item| item.identifier() == &root.join("gone.rs"))
+            .unwrap();
+        assert_eq!(gone.text.to_string(), "gone.rs ⚠ No longer exists");
+
+        let unreadable = explorer.load_dir(&root.join("README.md")).unwrap_err();
+        assert_eq!(unreadable.kind(), io::ErrorKind::NotADirectory);
+        assert!(matches!(
+            explorer.errors.get(&root.join("README.md")),
+            Some(ExplorerError::Unreadable { .. })
+        ));
+        // Kept until the directory is loaded again, unlike errors of missing entries
+        explorer.entries.remove(&root.join("gone.rs"));
+        explorer.rebuild_tree().unwrap();
+        assert!(explorer.errors.contains_key(&root.join("README.md")));
+        assert!(!explorer.errors.contains_key(&root.join("gone.rs")));
+        assert_eq!(explorer.load_dir(&root.join("src")).unwrap(), 0);
+    }
+
+    #[test]
+    fn go_up_from_unloaded_parent() {
+        let root = PathBuf::from("/project/src");
+        let file_system = InMemoryFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/README.md", "# Project");
+        let mut explorer = Explorer::new("up", &root).unwrap().file_system(file_system);
+        explorer.load_dir(&root).unwrap();
+
+        let mut state = ExplorerState::default();
+        assert!(explorer.go_up(&mut state).unwrap());
+        let items = explorer.tree.items();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].identifier(), &root);
+        assert_eq!(items[0].children().len(), 1);
+        assert_eq!(state.selected, core::slice::from_ref(&root));
+        assert!(state.expanded.contains(&vec![root]));
+    }
 }
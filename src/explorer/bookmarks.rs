@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::explorer::persist;
+
+/// Named bookmarks of paths, like the marks of vim.
+///
+/// Labels are single letters or any other text without tabs and line breaks.
+/// They are kept sorted so the marks `a` to `z` list in order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Bookmarks {
+    marks: BTreeMap<String, PathBuf>,
+}
+
+impl Bookmarks {
+    /// Bookmark the path under the given label.
+    ///
+    /// Returns the path the label pointed to before.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the label is empty or contains a tab or line break.
+    pub fn set<P>(&mut self, label: &str, path: P) -> io::Result<Option<PathBuf>>
+    where
+        P: Into<PathBuf>,
+    {
+        if label.is_empty() || label.contains(['\t', '\n', '\r']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid bookmark label {label:?}"),
+            ));
+        }
+        Ok(self.marks.insert(label.to_string(), path.into()))
+    }
+
+    /// Remove the bookmark, returning the path it pointed to.
+    pub fn remove(&mut self, label: &str) -> Option<PathBuf> {
+        self.marks.remove(label)
+    }
+
+    #[must_use]
+    pub fn get(&self, label: &str) -> Option<&Path> {
+        self.marks.get(label).map(PathBuf::as_path)
+    }
+
+    /// Bookmarks sorted by their label.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.marks
+            .iter()
+            .map(|(label, path)| (label.as_str(), path.as_path()))
+    }
+
+    /// Label of the bookmark at the given index of [`iter`](Self::iter),
+    /// like the one selected in a [`BookmarksPanel`](crate::bookmarks_panel::BookmarksPanel).
+    #[must_use]
+    pub fn label_at(&self, index: usize) -> Option<&str> {
+        self.marks.keys().nth(index).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.marks.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    /// Point the bookmarks of `from` and of the paths below it to their new place after a rename.
+    ///
+    /// Returns `true` when a bookmark changed.
+    pub fn rename_path(&mut self, from: &Path, to: &Path) -> bool {
+        let mut changed = false;
+        for path in self.marks.values_mut() {
+            if let Ok(relative) = path.strip_prefix(from) {
+                *path = if relative.as_os_str().is_empty() {
+                    to.to_path_buf()
+                } else {
+                    to.join(relative)
+                };
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Path of the bookmarks file of the given root, next to the state file of its
+    /// [`Session`](crate::explorer::session::Session).
+    ///
+    /// Returns `None` when neither `$XDG_STATE_HOME` nor `$HOME` is set.
+    #[must_use]
+    pub fn state_file(root: &Path) -> Option<PathBuf> {
+        persist::state_file(root, "bookmarks")
+    }
+
+    /// Save the bookmarks to the bookmarks file of the given root.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there is no state directory or the file can not be written.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = Self::state_file(root).ok_or_else(persist::no_state_dir)?;
+        self.save_to(root, &path)
+    }
+
+    /// Load the bookmarks from the bookmarks file of the given root.
+    /// There are no bookmarks when the file does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there is no state directory or the file can not be read or parsed.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let path = Self::state_file(root).ok_or_else(persist::no_state_dir)?;
+        match Self::load_from(root, &path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    /// Save the bookmarks to the given file, one `label<TAB>path` per line.
+    /// Paths below the root are stored relative to it, so loading the file for a moved root
+    /// keeps its bookmarks. The [`state_file`](Self::state_file) of a moved root is another one.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the file can not be written.
+    pub fn save_to(&self, root: &Path, path: &Path) -> io::Result<()> {
+        let mut contents = String::new();
+        for (label, bookmark) in self.iter() {
+            let relative = bookmark.strip_prefix(root).unwrap_or(bookmark);
+            contents.push_str(label);
+            contents.push('\t');
+            contents.push_str(&relative.to_string_lossy());
+            contents.push('\n');
+        }
+        persist::write_atomically(path, contents.as_bytes())
+    }
+
+    /// Load bookmarks saved with [`save_to`](Self::save_to).
+    ///
+    /// # Errors
+    ///
+    /// Errors when the file can not be read or a line has no tab.
+    pub fn load_from(root: &Path, path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut bookmarks = Self::default();
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let (label, bookmark) = line.split_once('\t').ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Bookmark without a tab: {line:?}"),
+                )
+            })?;
+            // Joining keeps absolute paths as they are
+            bookmarks.set(label, root.join(bookmark))?;
+        }
+        Ok(bookmarks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn set_and_rename() {
+        let mut bookmarks = Bookmarks::default();
+        assert_eq!(bookmarks.set("b", "/root/notes/b.md").unwrap(), None);
+        bookmarks.set("a", "/root/notes").unwrap();
+        bookmarks.set("todo", "/root/todo.md").unwrap();
+        assert_eq!(
+            bookmarks.set("b", "/root/b.md").unwrap(),
+            Some(PathBuf::from("/root/notes/b.md"))
+        );
+        assert!(bookmarks.set("", "/root").is_err());
+        assert!(bookmarks.set("a\tb", "/root").is_err());
+        assert_eq!(bookmarks.label_at(1), Some("b"));
+
+        bookmarks.set("b", "/root/notes/b.md").unwrap();
+        assert!(bookmarks.rename_path(Path::new("/root/notes"), Path::new("/root/archive")));
+        assert_eq!(bookmarks.get("a"), Some(Path::new("/root/archive")));
+        assert_eq!(bookmarks.get("b"), Some(Path::new("/root/archive/b.md")));
+        assert_eq!(bookmarks.get("todo"), Some(Path::new("/root/todo.md")));
+        assert!(!bookmarks.rename_path(Path::new("/root/note"), Path::new("/root/x")));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = TempDir::new("bookmarks");
+        let file = dir.join("state.bookmarks");
+        let root = Path::new("/vault");
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.set("a", "/vault/notes/a.md").unwrap();
+        bookmarks.set("elsewhere", "/tmp/b.md").unwrap();
+        bookmarks.save_to(root, &file).unwrap();
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "a\tnotes/a.md\nelsewhere\t/tmp/b.md\n"
+        );
+
+        // The bookmarks follow a moved root
+        let moved = Bookmarks::load_from(Path::new("/moved"), &file).unwrap();
+        assert_eq!(moved.get("a"), Some(Path::new("/moved/notes/a.md")));
+        assert_eq!(moved.get("elsewhere"), Some(Path::new("/tmp/b.md")));
+    }
+}
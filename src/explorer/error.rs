@@ -0,0 +1,158 @@
+use core::error::Error;
+use core::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::fs::FileSystem;
+
+/// Why an entry of the explorer can not be shown as a plain file or directory.
+///
+/// Kept per node in [`Explorer::errors`](crate::explorer::Explorer::errors),
+/// the node is rendered with an error marker and the message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExplorerError {
+    /// The entries of the directory can not be read, like without read permission
+    Unreadable {
+        path: PathBuf,
+        kind: io::ErrorKind,
+        message: String,
+    },
+    /// Reading the kind of the path is not permitted
+    PermissionDenied(PathBuf),
+    /// A symbolic link whose target does not exist
+    BrokenSymlink(PathBuf),
+    /// The path was removed after it was added
+    Vanished(PathBuf),
+    /// The path ends in `..` or is a root, so it has no name to show
+    NoFileName(PathBuf),
+    /// Any other error of the file system
+    Io {
+        path: PathBuf,
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl ExplorerError {
+    /// The error of reading the entries of a directory.
+    #[must_use]
+    pub fn unreadable(path: &Path, error: &io::Error) -> Self {
+        Self::Unreadable {
+            path: path.to_path_buf(),
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+
+    /// The error of reading the [`metadata`](FileSystem::metadata) of the path.
+    /// Tells broken symbolic links apart from vanished paths.
+    #[must_use]
+    pub fn metadata(fs: &dyn FileSystem, path: &Path, error: &io::Error) -> Self {
+        let path = path.to_path_buf();
+        match error.kind() {
+            io::ErrorKind::NotFound if fs.symlink_metadata(&path).is_ok() => {
+                Self::BrokenSymlink(path)
+            }
+            io::ErrorKind::NotFound => Self::Vanished(path),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(path),
+            kind => Self::Io {
+                path,
+                kind,
+                message: error.to_string(),
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Unreadable { path, .. }
+            | Self::PermissionDenied(path)
+            | Self::BrokenSymlink(path)
+            | Self::Vanished(path)
+            | Self::NoFileName(path)
+            | Self::Io { path, .. } => path,
+        }
+    }
+
+    /// Point the error at another path, like after its node was renamed.
+    pub fn set_path(&mut self, path: PathBuf) {
+        match self {
+            Self::Unreadable { path: old, .. }
+            | Self::PermissionDenied(old)
+            | Self::BrokenSymlink(old)
+            | Self::Vanished(old)
+            | Self::NoFileName(old)
+            | Self::Io { path: old, .. } => *old = path,
+        }
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::Unreadable { kind, .. } | Self::Io { kind, .. } => *kind,
+            Self::PermissionDenied(_) => io::ErrorKind::PermissionDenied,
+            Self::BrokenSymlink(_) | Self::Vanished(_) => io::ErrorKind::NotFound,
+            Self::NoFileName(_) => io::ErrorKind::InvalidInput,
+        }
+    }
+
+    /// The message shown next to the node, without the path.
+    #[must_use]
+    pub fn message(&self) -> String {
+        match self {
+            Self::Unreadable { message, .. } => format!("Can not be read: {message}"),
+            Self::PermissionDenied(_) => "Permission denied".to_string(),
+            Self::BrokenSymlink(_) => "Broken symbolic link".to_string(),
+            Self::Vanished(_) => "No longer exists".to_string(),
+            Self::NoFileName(_) => "Path has no file name".to_string(),
+            Self::Io { message, .. } => message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ExplorerError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}: {}", self.path().display(), self.message())
+    }
+}
+
+impl Error for ExplorerError {}
+
+impl From<ExplorerError> for io::Error {
+    fn from(error: ExplorerError) -> Self {
+        Self::new(error.kind(), error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::StdFs;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn classify() {
+        let dir = TempDir::new("error");
+        let missing = dir.join("missing");
+        let error = StdFs.metadata(&missing).unwrap_err();
+        assert_eq!(
+            ExplorerError::metadata(&StdFs, &missing, &error),
+            ExplorerError::Vanished(missing.clone())
+        );
+
+        #[cfg(unix)]
+        {
+            let link = dir.join("link");
+            std::os::unix::fs::symlink(&missing, &link).unwrap();
+            let error = StdFs.metadata(&link).unwrap_err();
+            let broken = ExplorerError::metadata(&StdFs, &link, &error);
+            assert_eq!(broken, ExplorerError::BrokenSymlink(link.clone()));
+            assert_eq!(
+                broken.to_string(),
+                format!("{}: Broken symbolic link", link.display())
+            );
+            assert_eq!(io::Error::from(broken).kind(), io::ErrorKind::NotFound);
+        }
+    }
+}
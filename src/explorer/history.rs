@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+
+/// Amount of jumps a [`History`] remembers by default.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 100;
+
+/// A bounded jump list of selections to go back and forward in, like in a browser.
+///
+/// Only jumps are recorded, moving to an adjacent node is not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<Identifier> {
+    /// Selections before the jumps, the latest last
+    back: VecDeque<Vec<Identifier>>,
+    /// Selections gone back from, the latest last
+    forward: Vec<Vec<Identifier>>,
+    capacity: usize,
+}
+
+impl<Identifier> Default for History<Identifier> {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl<Identifier> History<Identifier> {
+    /// Create an empty `History` remembering at most `capacity` jumps.
+    #[must_use]
+    pub const fn with_capacity(capacity: usize) -> Self {
+        Self {
+            back: VecDeque::new(),
+            forward: Vec::new(),
+            capacity,
+        }
+    }
+
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Selections to go back to, the latest last.
+    #[must_use]
+    pub const fn back_entries(&self) -> &VecDeque<Vec<Identifier>> {
+        &self.back
+    }
+
+    /// Selections to go forward to, the next one last.
+    #[must_use]
+    pub fn forward_entries(&self) -> &[Vec<Identifier>] {
+        &self.forward
+    }
+}
+
+impl<Identifier> History<Identifier>
+where
+    Identifier: Clone + PartialEq,
+{
+    /// Record a jump away from the given selection. Forgets the selections gone back from.
+    pub fn record(&mut self, from: Vec<Identifier>) {
+        if from.is_empty() || self.capacity == 0 {
+            return;
+        }
+        self.forward.clear();
+        if self.back.back() == Some(&from) {
+            return;
+        }
+        if self.back.len() >= self.capacity {
+            self.back.pop_front();
+        }
+        self.back.push_back(from);
+    }
+
+    /// Go back from the `current` selection to the latest one which still `exists`.
+    /// Selections which no longer exist are dropped.
+    pub fn back<F>(&mut self, current: Vec<Identifier>, exists: F) -> Option<Vec<Identifier>>
+    where
+        F: Fn(&[Identifier]) -> bool,
+    {
+        while let Some(target) = self.back.pop_back() {
+            if exists(&target) && target != current {
+                if !current.is_empty() {
+                    self.forward.push(current);
+                }
+                return Some(target);
+            }
+        }
+        None
+    }
+
+    /// Go forward from the `current` selection to the next one which still `exists`.
+    /// Selections which no longer exist are dropped.
+    pub fn forward<F>(&mut self, current: Vec<Identifier>, exists: F) -> Option<Vec<Identifier>>
+    where
+        F: Fn(&[Identifier]) -> bool,
+    {
+        while let Some(target) = self.forward.pop() {
+            if exists(&target) && target != current {
+                if !current.is_empty() {
+                    if self.back.len() >= self.capacity {
+                        self.back.pop_front();
+                    }
+                    self.back.push_back(current);
+                }
+                return Some(target);
+            }
+        }
+        None
+    }
+
+    /// Forget all selections.
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTS: fn(&[&str]) -> bool = |_| true;
+
+    #[test]
+    fn back_and_forward() {
+        let mut history = History::default();
+        history.record(vec!["a"]);
+        history.record(vec!["b"]);
+
+        assert_eq!(history.back(vec!["c"], EXISTS), Some(vec!["b"]));
+        assert_eq!(history.back(vec!["b"], EXISTS), Some(vec!["a"]));
+        assert_eq!(history.back(vec!["a"], EXISTS), None);
+        assert_eq!(history.forward(vec!["a"], EXISTS), Some(vec!["b"]));
+        assert_eq!(history.forward(vec!["b"], EXISTS), Some(vec!["c"]));
+        assert_eq!(history.forward(vec!["c"], EXISTS), None);
+
+        // A new jump forgets the way forward
+        history.back(vec!["c"], EXISTS);
+        history.record(vec!["b"]);
+        assert!(history.forward_entries().is_empty());
+    }
+
+    #[test]
+    fn bounded_and_skips_missing() {
+        let mut history = History::with_capacity(2);
+        history.record(vec!["a"]);
+        history.record(vec!["b"]);
+        history.record(vec!["gone"]);
+        assert_eq!(history.back_entries().len(), 2);
+
+        let exists = |identifier: &[&str]| identifier != ["gone"];
+        assert_eq!(history.back(vec!["c"], exists), Some(vec!["b"]));
+        assert_eq!(history.back(vec!["b"], exists), None);
+    }
+}
@@ -0,0 +1,84 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Path of the file keeping state of the given kind (its extension) for a root,
+/// below `$XDG_STATE_HOME/ki`, falling back to `~/.local/state/ki`.
+///
+/// Returns `None` when neither `$XDG_STATE_HOME` nor `$HOME` is set.
+pub fn state_file(root: &Path, extension: &str) -> Option<PathBuf> {
+    let dir = state_dir(env::var_os("XDG_STATE_HOME"), env::var_os("HOME"))?;
+    Some(dir.join(state_file_name(root, extension)))
+}
+
+pub fn no_state_dir() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "Neither XDG_STATE_HOME nor HOME is set",
+    )
+}
+
+/// Write next to the file first so a crash never leaves a half written file.
+/// Creates the directory of the file when needed.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(temporary, path)
+}
+
+fn state_dir(xdg_state_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let xdg_state_home = xdg_state_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute());
+    let base = xdg_state_home.or_else(|| {
+        home.filter(|home| !home.is_empty())
+            .map(|home| PathBuf::from(home).join(".local/state"))
+    })?;
+    Some(base.join("ki"))
+}
+
+/// File name of the state of a root: its name followed by a hash of the full path.
+fn state_file_name(root: &Path, extension: &str) -> String {
+    // FNV-1a, stable across Rust versions unlike the hashers of std
+    let hash = root
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    let name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("{name}-{hash:016x}.{extension}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_files() {
+        assert_eq!(
+            state_dir(Some("/state".into()), Some("/home/user".into())),
+            Some(PathBuf::from("/state/ki"))
+        );
+        assert_eq!(
+            state_dir(Some("relative".into()), Some("/home/user".into())),
+            Some(PathBuf::from("/home/user/.local/state/ki"))
+        );
+        assert_eq!(state_dir(None, None), None);
+
+        let name = state_file_name(Path::new("/home/user/vault"), "json");
+        assert!(name.starts_with("vault-"));
+        assert_eq!(Path::new(&name).extension(), Some("json".as_ref()));
+        assert_ne!(name, state_file_name(Path::new("/other/vault"), "json"));
+    }
+}
@@ -0,0 +1,341 @@
+use core::cell::{Cell, RefCell};
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Instant;
+
+use crate::fs::FileSystem;
+
+/// Amount of entries after which a [`Batch`] is sent.
+pub const DEFAULT_BATCH_SIZE: usize = 256;
+
+/// How long entries are held back at most before a [`Batch`] is sent, so progress stays visible.
+pub const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far a [`Scan`] got.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScanProgress {
+    pub dirs_visited: usize,
+    pub entries_found: usize,
+    /// Directories which could not be read
+    pub errors: usize,
+}
+
+impl fmt::Display for ScanProgress {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} entries in {} directories",
+            self.entries_found, self.dirs_visited
+        )?;
+        if self.errors > 0 {
+            write!(formatter, ", {} errors", self.errors)?;
+        }
+        Ok(())
+    }
+}
+
+/// A directory which could not be read by a [`Scan`].
+#[derive(Debug)]
+pub struct ScanError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+/// An entry found by a [`Scan`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScanEntry {
+    pub path: PathBuf,
+    /// Whether it is a directory, following symbolic links. `None` when its kind can not be read
+    pub is_dir: Option<bool>,
+}
+
+/// Entries found by the scanning thread since the previous batch.
+#[derive(Debug)]
+pub struct Batch {
+    pub entries: Vec<ScanEntry>,
+    pub errors: Vec<ScanError>,
+    /// Progress of the whole scan when the batch was sent
+    pub progress: ScanProgress,
+}
+
+/// Walks directories on a background thread and streams the entries found in [`Batch`]es.
+///
+/// Symbolic links are reported as entries but not followed.
+/// The thread stops when it is [`cancel`](Self::cancel)ed or the `Scan` is dropped.
+/// [`Explorer::start_scan`](crate::explorer::Explorer::start_scan) adds the entries to the
+/// explorer while they arrive.
+#[derive(Debug)]
+pub struct Scan {
+    roots: Vec<PathBuf>,
+    batches: Receiver<Batch>,
+    cancelled: Arc<AtomicBool>,
+    progress: Cell<ScanProgress>,
+    /// Errors received but not taken yet
+    errors: RefCell<Vec<ScanError>>,
+    finished: Cell<bool>,
+}
+
+impl Scan {
+    /// Start scanning below the roots on a new thread.
+    /// The file system is moved to the thread, like [`StdFs`](crate::fs::StdFs).
+    #[must_use]
+    pub fn spawn<F>(fs: F, roots: Vec<PathBuf>) -> Self
+    where
+        F: FileSystem + Send + 'static,
+    {
+        let (sender, batches) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let walker = Walker {
+            sender,
+            cancelled: Arc::clone(&cancelled),
+            batch: Vec::new(),
+            errors: Vec::new(),
+            progress: ScanProgress::default(),
+            sent: Instant::now(),
+        };
+        let pending = roots.clone();
+        thread::spawn(move || walker.walk(&fs, pending));
+
+        Self {
+            roots,
+            batches,
+            cancelled,
+            progress: Cell::new(ScanProgress::default()),
+            errors: RefCell::new(Vec::new()),
+            finished: Cell::new(false),
+        }
+    }
+
+    /// The directories being scanned.
+    #[must_use]
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Progress of the last [`Batch`] received.
+    #[must_use]
+    pub fn progress(&self) -> ScanProgress {
+        self.progress.get()
+    }
+
+    /// The thread has sent everything or was cancelled.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished.get()
+    }
+
+    /// Stop the thread at the next directory. Batches already sent can still be received.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Entries of the batches which arrived since the last call, without blocking.
+    /// Errors are kept for [`take_errors`](Self::take_errors).
+    pub fn receive(&self) -> Vec<ScanEntry> {
+        let mut entries = Vec::new();
+        loop {
+            match self.batches.try_recv() {
+                Ok(batch) => {
+                    entries.extend(batch.entries);
+                    self.errors.borrow_mut().extend(batch.errors);
+                    self.progress.set(batch.progress);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finished.set(true);
+                    break;
+                }
+            }
+        }
+        entries
+    }
+
+    /// The directories which could not be read since the last call.
+    pub fn take_errors(&self) -> Vec<ScanError> {
+        self.errors.take()
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// The state of the scanning thread.
+struct Walker {
+    sender: Sender<Batch>,
+    cancelled: Arc<AtomicBool>,
+    batch: Vec<ScanEntry>,
+    errors: Vec<ScanError>,
+    progress: ScanProgress,
+    sent: Instant,
+}
+
+impl Walker {
+    fn walk(mut self, fs: &dyn FileSystem, mut pending: Vec<PathBuf>) {
+        while let Some(dir) = pending.pop() {
+            if self.cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+            match fs.read_dir(&dir) {
+                Ok(mut children) => {
+                    self.progress.dirs_visited += 1;
+                    self.progress.entries_found += children.len();
+                    children.sort();
+                    let entries = children
+                        .into_iter()
+                        .map(|path| ScanEntry {
+                            is_dir: fs.metadata(&path).ok().map(|metadata| metadata.is_dir),
+                            path,
+                        })
+                        .collect::<Vec<_>>();
+                    // Reversed so the stack visits them in order. Links to directories are
+                    // not followed, they may point to an ancestor and never end
+                    pending.extend(
+                        entries
+                            .iter()
+                            .rev()
+                            .filter(|entry| {
+                                entry.is_dir == Some(true)
+                                    && fs
+                                        .symlink_metadata(&entry.path)
+                                        .is_ok_and(|metadata| metadata.is_dir)
+                            })
+                            .map(|entry| entry.path.clone()),
+                    );
+                    self.batch.extend(entries);
+                }
+                Err(error) => {
+                    self.progress.errors += 1;
+                    self.errors.push(ScanError { path: dir, error });
+                }
+            }
+            if (self.batch.len() >= DEFAULT_BATCH_SIZE || self.sent.elapsed() >= BATCH_INTERVAL)
+                && !self.send()
+            {
+                return;
+            }
+        }
+        self.send();
+    }
+
+    /// Returns `false` when nobody receives anymore.
+    fn send(&mut self) -> bool {
+        self.sent = Instant::now();
+        let batch = Batch {
+            entries: core::mem::take(&mut self.batch),
+            errors: core::mem::take(&mut self.errors),
+            progress: self.progress,
+        };
+        self.sender.send(batch).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::fs::memory::InMemoryFs;
+    use crate::temp_dir::TempDir;
+
+    /// Receive until the scan finished.
+    fn receive_all(scan: &Scan) -> Vec<ScanEntry> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut entries = Vec::new();
+        while !scan.is_finished() {
+            assert!(Instant::now() < deadline, "Scan did not finish");
+            entries.extend(scan.receive());
+            thread::sleep(Duration::from_millis(1));
+        }
+        entries
+    }
+
+    #[test]
+    fn streams_entries() {
+        let file_system = InMemoryFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/src/fs/mod.rs", "")
+            .with_file("/project/README.md", "# Project");
+        let scan = Scan::spawn(
+            file_system,
+            vec![PathBuf::from("/project"), PathBuf::from("/missing")],
+        );
+        let mut entries = receive_all(&scan);
+        entries.sort_by(|left, right| left.path.cmp(&right.path));
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.path.to_str().unwrap(), entry.is_dir))
+                .collect::<Vec<_>>(),
+            [
+                ("/project/README.md", Some(false)),
+                ("/project/src", Some(true)),
+                ("/project/src/fs", Some(true)),
+                ("/project/src/fs/mod.rs", Some(false)),
+                ("/project/src/main.rs", Some(false)),
+            ]
+        );
+        assert_eq!(
+            scan.progress(),
+            ScanProgress {
+                dirs_visited: 3,
+                entries_found: 5,
+                errors: 1,
+            }
+        );
+        let errors = scan.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, Path::new("/missing"));
+        assert_eq!(errors[0].error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn cancel() {
+        let scan = Scan::spawn(
+            InMemoryFs::new().with_dir("/a/b/c"),
+            vec![PathBuf::from("/a")],
+        );
+        scan.cancel();
+        receive_all(&scan);
+        assert!(scan.is_cancelled());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn does_not_follow_links() {
+        let dir = TempDir::new("scan-loop");
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        std::os::unix::fs::symlink("..", dir.join("sub/loop")).unwrap();
+
+        let scan = Scan::spawn(crate::fs::StdFs, vec![dir.to_path_buf()]);
+        let mut entries = receive_all(&scan);
+        entries.sort_by(|left, right| left.path.cmp(&right.path));
+        assert_eq!(
+            entries,
+            [
+                ScanEntry {
+                    path: dir.join("sub"),
+                    is_dir: Some(true),
+                },
+                ScanEntry {
+                    path: dir.join("sub/loop"),
+                    is_dir: Some(true),
+                },
+            ]
+        );
+        assert_eq!(scan.progress().dirs_visited, 2);
+    }
+}
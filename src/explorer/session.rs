@@ -0,0 +1,233 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::explorer::Explorer;
+use crate::explorer::persist;
+use crate::explorer::state::ExplorerState;
+use crate::fs::PathLike;
+
+/// The parts of an [`ExplorerState`] worth keeping between sessions of an app.
+///
+/// Paths are stored relative to the [`root_path`](Explorer::root_path).
+/// Nodes which no longer exist are pruned on [`restore`](Self::restore).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// The selected node
+    pub selected: Option<PathBuf>,
+    /// The expanded nodes, sorted
+    pub expanded: Vec<PathBuf>,
+    pub offset: usize,
+    pub horizontal_offset: usize,
+}
+
+impl Session {
+    /// Capture the session of the given state.
+    #[must_use]
+    pub fn capture<T>(explorer: &Explorer<'_, T>, state: &ExplorerState<T>) -> Self
+    where
+        T: PathLike,
+    {
+        let relative = |identifier: &[T]| {
+            identifier.last().map(|path| {
+                path.as_ref()
+                    .strip_prefix(explorer.root_path.as_ref())
+                    .unwrap_or_else(|_| path.as_ref())
+                    .to_path_buf()
+            })
+        };
+
+        let mut expanded = state
+            .expanded()
+            .iter()
+            .filter_map(|identifier| relative(identifier))
+            .collect::<Vec<_>>();
+        expanded.sort();
+
+        Self {
+            selected: relative(state.selected()),
+            expanded,
+            offset: state.offset,
+            horizontal_offset: state.horizontal_offset,
+        }
+    }
+
+    /// Apply the session to the given state.
+    /// Nodes which are not among the [`entries`](Explorer::entries) any more are left out,
+    /// a selected node which no longer exists is replaced by its closest existing ancestor.
+    ///
+    /// Returns `true` when something was pruned.
+    pub fn restore<T>(&self, explorer: &Explorer<'_, T>, state: &mut ExplorerState<T>) -> bool
+    where
+        T: PathLike,
+    {
+        let exists = |identifier: &[T]| identifier.iter().all(|path| explorer.contains(path));
+
+        let mut pruned = false;
+        state.expanded.clear();
+        for path in &self.expanded {
+            match explorer.identifier(path) {
+                Some(identifier) if exists(&identifier) => {
+                    state.expand(identifier);
+                }
+                _ => pruned = true,
+            }
+        }
+
+        let mut selected = self
+            .selected
+            .as_ref()
+            .and_then(|path| explorer.identifier(path))
+            .unwrap_or_default();
+        while !selected.is_empty() && !exists(&selected) {
+            selected.pop();
+            pruned = true;
+        }
+        state.select(selected);
+
+        state.offset = self.offset;
+        state.horizontal_offset = self.horizontal_offset;
+        pruned
+    }
+
+    /// Path of the state file of the given root below `$XDG_STATE_HOME/ki`,
+    /// falling back to `~/.local/state/ki`. The file is named after the full path of the root,
+    /// a root which was moved starts without a session.
+    ///
+    /// Returns `None` when neither `$XDG_STATE_HOME` nor `$HOME` is set.
+    #[must_use]
+    pub fn state_file(root: &Path) -> Option<PathBuf> {
+        persist::state_file(root, "json")
+    }
+
+    /// Save the session to the state file of the given root.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there is no state directory or the file can not be written.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = Self::state_file(root).ok_or_else(persist::no_state_dir)?;
+        self.save_to(&path)
+    }
+
+    /// Load the session from the state file of the given root.
+    ///
+    /// Returns `None` when there is no session for the root yet.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there is no state directory or the file can not be read or parsed.
+    pub fn load(root: &Path) -> io::Result<Option<Self>> {
+        let path = Self::state_file(root).ok_or_else(persist::no_state_dir)?;
+        match Self::load_from(&path) {
+            Ok(session) => Ok(Some(session)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Save the session as JSON to the given file, creating its directory when needed.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the file can not be written.
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        persist::write_atomically(path, json.as_bytes())
+    }
+
+    /// Load a session saved with [`save_to`](Self::save_to).
+    ///
+    /// # Errors
+    ///
+    /// Errors when the file can not be read or parsed.
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(io::Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn explorer(root: &PathBuf) -> Explorer<'_, PathBuf> {
+        let mut explorer = Explorer::new("session", root).unwrap();
+        explorer
+            .add_entries([
+                root.join("a"),
+                root.join("a/b"),
+                root.join("a/b/c.md"),
+                root.join("d.md"),
+            ])
+            .unwrap();
+        explorer
+    }
+
+    #[test]
+    fn capture_and_restore() {
+        let root = PathBuf::from("/ki-session");
+        let explorer = explorer(&root);
+
+        let mut state = ExplorerState::default();
+        state.expand(vec![root.join("a")]);
+        state.expand(vec![root.join("a"), root.join("a/b")]);
+        state.select(vec![
+            root.join("a"),
+            root.join("a/b"),
+            root.join("a/b/c.md"),
+        ]);
+        state.offset = 1;
+
+        let session = Session::capture(&explorer, &state);
+        assert_eq!(
+            session,
+            Session {
+                selected: Some(PathBuf::from("a/b/c.md")),
+                expanded: vec![PathBuf::from("a"), PathBuf::from("a/b")],
+                offset: 1,
+                horizontal_offset: 0,
+            }
+        );
+
+        let mut restored = ExplorerState::default();
+        assert!(!session.restore(&explorer, &mut restored));
+        assert_eq!(restored.selected, state.selected);
+        assert_eq!(restored.expanded, state.expanded);
+        assert_eq!(restored.offset, 1);
+    }
+
+    #[test]
+    fn prunes_missing_nodes() {
+        let root = PathBuf::from("/ki-session");
+        let explorer = explorer(&root);
+        let session = Session {
+            selected: Some(PathBuf::from("a/gone/e.md")),
+            expanded: vec![PathBuf::from("a"), PathBuf::from("gone")],
+            offset: 0,
+            horizontal_offset: 0,
+        };
+
+        let mut state = ExplorerState::default();
+        assert!(session.restore(&explorer, &mut state));
+        assert_eq!(state.selected, [root.join("a")]);
+        assert_eq!(state.expanded.len(), 1);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = TempDir::new("session");
+        let file = dir.join("state.json");
+        let session = Session {
+            selected: Some(PathBuf::from("d.md")),
+            expanded: vec![PathBuf::from("a")],
+            offset: 3,
+            horizontal_offset: 2,
+        };
+        session.save_to(&file).unwrap();
+        assert_eq!(Session::load_from(&file).unwrap(), session);
+    }
+}
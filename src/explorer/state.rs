@@ -1,18 +1,24 @@
+use core::fmt::Debug;
+use core::hash::Hash;
 use std::collections::HashSet;
-use std::fmt::Debug;
-use std::hash::Hash;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
 use ratatui::layout::{Position, Rect};
 
+use crate::explorer::bookmarks::Bookmarks;
+use crate::explorer::history::History;
 use crate::tree::flatten::{Flattened, flatten};
 use crate::tree::item::TreeItem;
 
+/// Amount of nodes the recursive expand operations of [`ExplorerState`] open at most by default.
+pub const DEFAULT_EXPAND_LIMIT: usize = 10_000;
+
 /// TODO
-#[derive(Debug, Default, Clone, Eq)]
+#[derive(Debug, Clone, Eq)]
 pub struct ExplorerState<Identifier>
 where
-    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
 {
     /// Contains the selected node
     pub selected: Vec<Identifier>,
@@ -24,21 +30,63 @@ where
     pub open: bool,
 
     pub offset: usize,
+    /// Amount of columns the content is scrolled to the right
+    pub horizontal_offset: usize,
     pub last_area: Rect,
     pub last_biggest_index: usize,
+    /// Width of the widest row (without highlight symbol) on last render
+    pub last_content_width: usize,
+    /// Width available for the content of a row (without highlight symbol) on last render
+    pub last_content_viewport: usize,
     /// All identifiers open on last render
     pub last_identifiers: Vec<Vec<Identifier>>,
+    /// Identifiers open on last render which have children
+    pub last_parents: HashSet<Vec<Identifier>>,
     /// Identifier rendered at `y` on last render
     pub last_rendered_identifiers: Vec<(u16, Vec<Identifier>)>,
+    /// Identifiers of the folders merged into compacted rows and where they were rendered on last render
+    pub last_rendered_segments: Vec<(Rect, Vec<Identifier>)>,
     pub ensure_selected_in_view_on_next_render: bool,
+    /// Selections before jumps, see [`jump`](Self::jump)
+    pub history: History<Identifier>,
+    /// Named bookmarks, see [`mark`](Self::mark) and
+    /// [`Explorer::jump_to_bookmark`](crate::explorer::Explorer::jump_to_bookmark)
+    pub bookmarks: Bookmarks,
+}
+
+impl<Identifier> Default for ExplorerState<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
+{
+    fn default() -> Self {
+        Self {
+            selected: Vec::new(),
+            expanded: HashSet::new(),
+            open: false,
+            offset: 0,
+            horizontal_offset: 0,
+            last_area: Rect::default(),
+            last_biggest_index: 0,
+            last_content_width: 0,
+            last_content_viewport: 0,
+            last_identifiers: Vec::new(),
+            last_parents: HashSet::new(),
+            last_rendered_identifiers: Vec::new(),
+            last_rendered_segments: Vec::new(),
+            ensure_selected_in_view_on_next_render: false,
+            history: History::default(),
+            bookmarks: Bookmarks::default(),
+        }
+    }
 }
 
 impl<Identifier> PartialEq for ExplorerState<Identifier>
 where
-    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
 {
     fn eq(&self, other: &Self) -> bool {
         self.offset == other.offset
+            && self.horizontal_offset == other.horizontal_offset
             && self.expanded.len() == other.expanded.len()
             && self
                 .expanded
@@ -49,33 +97,42 @@ where
                 == other.ensure_selected_in_view_on_next_render
             && self.last_area == other.last_area
             && self.last_biggest_index == other.last_biggest_index
+            && self.last_content_width == other.last_content_width
+            && self.last_content_viewport == other.last_content_viewport
             && self.last_identifiers == other.last_identifiers
+            && self.last_parents == other.last_parents
             && self.last_rendered_identifiers == other.last_rendered_identifiers
+            && self.last_rendered_segments == other.last_rendered_segments
             && self.open == other.open
+            && self.history == other.history
+            && self.bookmarks == other.bookmarks
     }
 }
 
 impl<Identifier> ExplorerState<Identifier>
 where
-    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
 {
     /// TODO
+    #[must_use]
     pub const fn get_offset(&self) -> usize {
         self.offset
     }
 
     /// TODO
+    #[must_use]
     pub const fn expanded(&self) -> &HashSet<Vec<Identifier>> {
         &self.expanded
     }
 
     /// Return the currently selected node
-    #[allow(clippy::missing_const_for_fn)] // False positive
-    pub fn selected(&self) -> &Vec<Identifier> {
+    #[must_use]
+    pub const fn selected(&self) -> &Vec<Identifier> {
         &self.selected
     }
 
     /// Get a flat list of all currently viewable (including by scrolling) [`TreeItem`]s with this `ExplorerState`.
+    #[must_use]
     pub fn flatten<'text>(
         &self,
         items: Vec<TreeItem<'text, Identifier>>,
@@ -91,6 +148,48 @@ where
         changed
     }
 
+    /// Select the given identifier and remember the current selection in the [`history`](Self::history).
+    /// Meant for jumps to non-adjacent nodes, like accepting a search result.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn jump(&mut self, identifier: Vec<Identifier>) -> bool {
+        if identifier != self.selected {
+            self.history.record(self.selected.clone());
+        }
+        self.select(identifier)
+    }
+
+    /// Go back to the selection before the last jump, expanding its ancestors.
+    /// Selections no longer among the given items are skipped.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn back(&mut self, items: &[TreeItem<'_, Identifier>]) -> bool {
+        let current = self.selected.clone();
+        let target = self
+            .history
+            .back(current, |identifier| find_item(items, identifier).is_some());
+        target.is_some_and(|target| self.restore(target))
+    }
+
+    /// Go forward to the selection gone [`back`](Self::back) from, expanding its ancestors.
+    /// Selections no longer among the given items are skipped.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn forward(&mut self, items: &[TreeItem<'_, Identifier>]) -> bool {
+        let current = self.selected.clone();
+        let target = self
+            .history
+            .forward(current, |identifier| find_item(items, identifier).is_some());
+        target.is_some_and(|target| self.restore(target))
+    }
+
+    fn restore(&mut self, identifier: Vec<Identifier>) -> bool {
+        for depth in 1..identifier.len() {
+            self.expand(identifier[..depth].to_vec());
+        }
+        self.select(identifier)
+    }
+
     /// Expand a tree node.
     /// Returns `true` when it was collapsed and has been expanded.
     /// Returns `false` when it was already expanded.
@@ -127,6 +226,9 @@ where
     /// Toggles the currently selected tree node expanded/collapsed state.
     /// See also [`toggle`](Self::toggle)
     ///
+    /// A folder merged into a compacted row toggles that row, as only its innermost folder
+    /// can be expanded.
+    ///
     /// Returns `true` when a node is expanded / collapsed.
     /// As toggle always changes something, this only returns `false` when nothing is selected.
     pub fn toggle_selected(&mut self) -> bool {
@@ -136,12 +238,17 @@ where
 
         self.ensure_selected_in_view_on_next_render = true;
 
-        let was_expanded = self.expanded.remove(&self.selected);
+        let row = self
+            .selected_position()
+            .map(|position| self.last_identifiers[position].clone())
+            .filter(|row| row.starts_with(&self.selected))
+            .unwrap_or_else(|| self.selected.clone());
+        let was_expanded = self.expanded.remove(&row);
         if was_expanded {
             return true;
         }
 
-        self.expand(self.selected.clone())
+        self.expand(row)
     }
 
     /// Collapses all expanded nodes.
@@ -156,12 +263,151 @@ where
         }
     }
 
+    /// Expand every node of the given items.
+    ///
+    /// Returns `true` when any node was expanded.
+    ///
+    /// # Errors
+    ///
+    /// Errors without expanding anything when more than `limit` nodes would be expanded.
+    pub fn expand_all(
+        &mut self,
+        items: &[TreeItem<'_, Identifier>],
+        limit: usize,
+    ) -> io::Result<bool> {
+        let mut parents = Vec::new();
+        collect_parents(items, &[], None, limit, &mut parents)?;
+        Ok(self.expand_each(parents))
+    }
+
+    /// Expand the node with the given identifier and its descendants up to `max_depth` levels
+    /// below it, all of them when `None`.
+    ///
+    /// Returns `true` when any node was expanded.
+    ///
+    /// # Errors
+    ///
+    /// Errors without expanding anything when the identifier is not among the items
+    /// or more than `limit` nodes would be expanded.
+    pub fn expand_recursive(
+        &mut self,
+        items: &[TreeItem<'_, Identifier>],
+        identifier: &[Identifier],
+        max_depth: Option<usize>,
+        limit: usize,
+    ) -> io::Result<bool> {
+        let item = find_item(items, identifier).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Identifier is not in the tree")
+        })?;
+        let mut parents = Vec::new();
+        if !item.children().is_empty() {
+            if limit == 0 {
+                return Err(limit_exceeded(limit));
+            }
+            parents.push(identifier.to_vec());
+            if max_depth != Some(0) {
+                let max_depth = max_depth.map(|depth| depth - 1);
+                collect_parents(
+                    item.children(),
+                    identifier,
+                    max_depth,
+                    limit - 1,
+                    &mut parents,
+                )?;
+            }
+        }
+        Ok(self.expand_each(parents))
+    }
+
+    /// Expand exactly the nodes above the given level, so nodes up to depth `level` are visible.
+    /// Level 0 collapses everything.
+    ///
+    /// Returns `true` when the state changed.
+    ///
+    /// # Errors
+    ///
+    /// Errors without changing anything when more than `limit` nodes would be expanded.
+    pub fn expand_to_level(
+        &mut self,
+        items: &[TreeItem<'_, Identifier>],
+        level: usize,
+        limit: usize,
+    ) -> io::Result<bool> {
+        let mut parents = Vec::new();
+        if level > 0 {
+            collect_parents(items, &[], Some(level - 1), limit, &mut parents)?;
+        }
+        let expanded = parents.into_iter().collect::<HashSet<_>>();
+        let changed = expanded != self.expanded;
+        self.expanded = expanded;
+        Ok(changed)
+    }
+
+    fn expand_each(&mut self, identifiers: Vec<Vec<Identifier>>) -> bool {
+        let mut changed = false;
+        for identifier in identifiers {
+            changed |= self.expand(identifier);
+        }
+        changed
+    }
+
+    /// Collapse the node with the given identifier and every node expanded below it.
+    ///
+    /// Returns `true` when any node was collapsed.
+    pub fn collapse_recursive(&mut self, identifier: &[Identifier]) -> bool {
+        let before = self.expanded.len();
+        self.expanded
+            .retain(|expanded| !expanded.starts_with(identifier));
+        self.expanded.len() != before
+    }
+
+    /// Collapse the siblings of the selected node and everything expanded below them.
+    ///
+    /// Returns `true` when any node was collapsed.
+    pub fn collapse_siblings(&mut self) -> bool {
+        let depth = self.selected.len();
+        let Some(parent) = depth
+            .checked_sub(1)
+            .map(|parent_depth| &self.selected[..parent_depth])
+        else {
+            return false;
+        };
+        let before = self.expanded.len();
+        self.expanded.retain(|expanded| {
+            !(expanded.len() >= depth
+                && expanded.starts_with(parent)
+                && expanded[..depth] != self.selected[..])
+        });
+        self.expanded.len() != before
+    }
+
+    /// Select the given ancestor of the selected node and collapse it
+    /// together with everything expanded below it, like when clicking it in a breadcrumb.
+    ///
+    /// An empty identifier stands for the root: everything is collapsed
+    /// and the top level ancestor of the selected node is selected.
+    ///
+    /// Returns `true` when the state changed.
+    pub fn collapse_to(&mut self, identifier: Vec<Identifier>) -> bool {
+        if identifier.is_empty() {
+            let top_level = self.selected.iter().take(1).cloned().collect();
+            let collapsed = self.collapse_all();
+            return self.jump(top_level) || collapsed;
+        }
+
+        let expanded_before = self.expanded.len();
+        self.expanded
+            .retain(|expanded| !expanded.starts_with(&identifier));
+        let collapsed = self.expanded.len() != expanded_before;
+        self.jump(identifier) || collapsed
+    }
+
     /// Select the first node.
     ///
     /// Returns `true` when the selection changed.
     pub fn select_first(&mut self) -> bool {
         let identifier = self.last_identifiers.first().cloned().unwrap_or_default();
-        self.select(identifier)
+        self.jump(identifier)
     }
 
     /// Select the last node.
@@ -169,7 +415,7 @@ where
     /// Returns `true` when the selection changed.
     pub fn select_last(&mut self) -> bool {
         let new_identifier = self.last_identifiers.last().cloned().unwrap_or_default();
-        self.select(new_identifier)
+        self.jump(new_identifier)
     }
 
     /// TODO
@@ -178,10 +424,7 @@ where
             return false;
         }
 
-        let current_pos = self
-            .last_identifiers
-            .iter()
-            .position(|id| id == &self.selected);
+        let current_pos = self.selected_position();
 
         let new_pos = current_pos
             .map_or(0, |pos| pos.saturating_add(1))
@@ -209,10 +452,7 @@ where
             return false;
         }
 
-        let current_pos = self
-            .last_identifiers
-            .iter()
-            .position(|id| id == &self.selected);
+        let current_pos = self.selected_position();
 
         let new_pos = current_pos
             .map_or(usize::MAX, |pos| pos.saturating_sub(1))
@@ -234,12 +474,198 @@ where
         }
     }
 
+    /// Select the parent of the selected node.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_parent(&mut self) -> bool {
+        if self.selected.len() < 2 {
+            return false;
+        }
+        let parent = self.selected[..self.selected.len() - 1].to_vec();
+        self.select(parent)
+    }
+
+    /// Select the first child of the selected node when it is expanded.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_first_child(&mut self) -> bool {
+        let Some(position) = self.selected_position() else {
+            return false;
+        };
+        let depth = self.selected.len();
+        // A folder merged into a compacted row has its child in the same row
+        let row = &self.last_identifiers[position];
+        let child = if row.len() > depth {
+            Some(row[..=depth].to_vec())
+        } else {
+            self.last_identifiers
+                .get(position + 1)
+                .filter(|next| next.len() > depth && next.starts_with(&self.selected))
+                .map(|next| next[..=depth].to_vec())
+        };
+        child.is_some_and(|child| self.select(child))
+    }
+
+    /// Select the next node with the same parent as the selected node.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_next_sibling(&mut self) -> bool {
+        let Some(position) = self.selected_position() else {
+            return false;
+        };
+        let sibling = self.sibling(self.last_identifiers.iter().skip(position + 1));
+        sibling.is_some_and(|sibling| self.select(sibling))
+    }
+
+    /// Select the previous node with the same parent as the selected node.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_prev_sibling(&mut self) -> bool {
+        let Some(position) = self.selected_position() else {
+            return false;
+        };
+        let sibling = self.sibling(self.last_identifiers[..position].iter().rev());
+        sibling.is_some_and(|sibling| self.select(sibling))
+    }
+
+    /// First sibling of the selected node among the given identifiers,
+    /// which walk away from the selected node.
+    fn sibling<'identifier, I>(&self, identifiers: I) -> Option<Vec<Identifier>>
+    where
+        I: Iterator<Item = &'identifier Vec<Identifier>>,
+        Identifier: 'identifier,
+    {
+        let depth = self.selected.len();
+        let parent = self.selected.get(..depth.checked_sub(1)?)?;
+        identifiers
+            .take_while(|identifier| identifier.starts_with(parent))
+            .find(|identifier| identifier.len() >= depth && identifier[..depth] != self.selected)
+            .map(|identifier| identifier[..depth].to_vec())
+    }
+
+    /// Select the next node below the selected one which has children.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_next_directory(&mut self) -> bool {
+        let start = self.selected_position().map_or(0, |position| position + 1);
+        let directory = self
+            .last_identifiers
+            .iter()
+            .skip(start)
+            .find(|identifier| self.last_parents.contains(*identifier))
+            .cloned();
+        directory.is_some_and(|directory| self.select(directory))
+    }
+
+    /// Select the node a page below the selected one, a page being the height of the last render.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn page_down(&mut self) -> bool {
+        self.select_by(self.page_height(), true)
+    }
+
+    /// Select the node a page above the selected one, a page being the height of the last render.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn page_up(&mut self) -> bool {
+        self.select_by(self.page_height(), false)
+    }
+
+    /// Select the node half a page below the selected one.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn half_page_down(&mut self) -> bool {
+        self.select_by((self.page_height() / 2).max(1), true)
+    }
+
+    /// Select the node half a page above the selected one.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn half_page_up(&mut self) -> bool {
+        self.select_by((self.page_height() / 2).max(1), false)
+    }
+
+    fn page_height(&self) -> usize {
+        (self.last_area.height as usize).max(1)
+    }
+
+    /// Select the node `rows` below or above the selected one, stopping at the first and last node.
+    fn select_by(&mut self, rows: usize, down: bool) -> bool {
+        if self.last_identifiers.is_empty() {
+            return false;
+        }
+        let position = self.selected_position().map_or(0, |position| {
+            let position = if down {
+                position.saturating_add(rows)
+            } else {
+                position.saturating_sub(rows)
+            };
+            position.min(self.last_identifiers.len() - 1)
+        });
+        let identifier = self.last_identifiers[position].clone();
+        self.selected != identifier && self.select(identifier)
+    }
+
+    /// Collapse the selected node when it is expanded, otherwise select its parent.
+    /// Meant for the left arrow key.
+    ///
+    /// Returns `true` when the state changed.
+    pub fn collapse_or_select_parent(&mut self) -> bool {
+        if self.expanded.contains(&self.selected) {
+            let selected = self.selected.clone();
+            self.collapse(&selected)
+        } else {
+            self.select_parent()
+        }
+    }
+
+    /// Expand the selected node when it is collapsed, otherwise select its first child.
+    /// Meant for the right arrow key.
+    ///
+    /// Returns `true` when the state changed.
+    pub fn expand_or_select_first_child(&mut self) -> bool {
+        if self.expanded.contains(&self.selected) {
+            self.select_first_child()
+        } else if self.last_parents.contains(&self.selected) {
+            self.expand(self.selected.clone())
+        } else {
+            false
+        }
+    }
+
+    /// Position of the selected node in [`last_identifiers`](Self::last_identifiers).
+    ///
+    /// A folder merged into a compacted row is at the position of that row.
+    #[must_use]
+    pub fn selected_position(&self) -> Option<usize> {
+        if self.selected.is_empty() {
+            return None;
+        }
+        self.last_identifiers
+            .iter()
+            .position(|identifier| identifier == &self.selected)
+            .or_else(|| {
+                self.last_identifiers
+                    .iter()
+                    .position(|identifier| identifier.starts_with(&self.selected))
+            })
+    }
+
     /// Get the identifier that was rendered for the given position on last render.
+    #[must_use]
     pub fn rendered_at(&self, position: Position) -> Option<&[Identifier]> {
         if !self.last_area.contains(position) {
             return None;
         }
 
+        if let Some((_, identifier)) = self
+            .last_rendered_segments
+            .iter()
+            .find(|(area, _)| area.contains(position))
+        {
+            return Some(identifier);
+        }
+
         self.last_rendered_identifiers
             .iter()
             .rev()
@@ -265,7 +691,7 @@ where
     }
 
     /// Ensure the selected [`TreeItem`] is in view on next render
-    pub fn scroll_selected_into_view(&mut self) {
+    pub const fn scroll_selected_into_view(&mut self) {
         self.ensure_selected_in_view_on_next_render = true;
     }
 
@@ -273,7 +699,7 @@ where
     ///
     /// Returns `true` when the scroll position changed.
     /// Returns `false` when the scrolling has reached the top.
-    pub fn scroll_up(&mut self, lines: usize) -> bool {
+    pub const fn scroll_up(&mut self, lines: usize) -> bool {
         let before = self.offset;
         self.offset = self.offset.saturating_sub(lines);
         before != self.offset
@@ -291,4 +717,347 @@ where
             .min(self.last_biggest_index);
         before != self.offset
     }
+
+    /// Scroll the specified amount of columns to the left
+    ///
+    /// Returns `true` when the scroll position changed.
+    /// Returns `false` when the scrolling has reached the start of the rows.
+    pub const fn scroll_left(&mut self, columns: usize) -> bool {
+        let before = self.horizontal_offset;
+        self.horizontal_offset = self.horizontal_offset.saturating_sub(columns);
+        before != self.horizontal_offset
+    }
+
+    /// Scroll the specified amount of columns to the right
+    ///
+    /// Returns `true` when the scroll position changed.
+    /// Returns `false` when the scrolling has reached the end of the widest row.
+    pub fn scroll_right(&mut self, columns: usize) -> bool {
+        let before = self.horizontal_offset;
+        self.horizontal_offset = self.horizontal_offset.saturating_add(columns).min(
+            self.last_content_width
+                .saturating_sub(self.last_content_viewport),
+        );
+        before != self.horizontal_offset
+    }
+}
+
+impl<Identifier> ExplorerState<Identifier>
+where
+    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+{
+    /// Bookmark the selected node under the given label, like `m` followed by a letter in vim.
+    ///
+    /// Returns the path the label pointed to before.
+    ///
+    /// # Errors
+    ///
+    /// Errors when nothing is selected or the label is invalid, see [`Bookmarks::set`].
+    pub fn mark(&mut self, label: &str) -> io::Result<Option<PathBuf>> {
+        let selected = self.selected.last().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Nothing is selected to bookmark")
+        })?;
+        self.bookmarks.set(label, selected.as_ref())
+    }
+}
+
+fn limit_exceeded(limit: usize) -> io::Error {
+    io::Error::other(format!("Expanding would open more than {limit} nodes"))
+}
+
+/// Find the item with the given identifier.
+fn find_item<'items, 'text, Identifier>(
+    items: &'items [TreeItem<'text, Identifier>],
+    identifier: &[Identifier],
+) -> Option<&'items TreeItem<'text, Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + Hash,
+{
+    let (first, rest) = identifier.split_first()?;
+    let item = items.iter().find(|item| item.identifier() == first)?;
+    if rest.is_empty() {
+        Some(item)
+    } else {
+        find_item(item.children(), rest)
+    }
+}
+
+/// Collect the identifiers of the items with children below `parent`, down to `max_depth`
+/// levels (all when `None`). Stops with an error as soon as there are more than `limit`.
+fn collect_parents<Identifier>(
+    items: &[TreeItem<'_, Identifier>],
+    parent: &[Identifier],
+    max_depth: Option<usize>,
+    limit: usize,
+    parents: &mut Vec<Vec<Identifier>>,
+) -> io::Result<()>
+where
+    Identifier: Clone + PartialEq + Eq + Hash,
+{
+    for item in items.iter().filter(|item| !item.children().is_empty()) {
+        let mut identifier = parent.to_vec();
+        identifier.push(item.identifier().clone());
+        if parents.len() >= limit {
+            return Err(limit_exceeded(limit));
+        }
+        parents.push(identifier.clone());
+        if max_depth != Some(0) {
+            let max_depth = max_depth.map(|depth| depth - 1);
+            collect_parents(item.children(), &identifier, max_depth, limit, parents)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// State as rendered for this tree:
+    ///
+    /// ```text
+    /// ▼ a
+    ///   ▶ b
+    ///     c
+    ///   ▼ d
+    ///       e
+    ///   f
+    /// ```
+    fn rendered() -> ExplorerState<&'static str> {
+        ExplorerState {
+            expanded: [vec!["a"], vec!["a", "d"]].into(),
+            last_area: Rect::new(0, 0, 10, 4),
+            last_biggest_index: 5,
+            last_identifiers: vec![
+                vec!["a"],
+                vec!["a", "b"],
+                vec!["a", "c"],
+                vec!["a", "d"],
+                vec!["a", "d", "e"],
+                vec!["f"],
+            ],
+            last_parents: [vec!["a"], vec!["a", "b"], vec!["a", "d"]].into(),
+            ..ExplorerState::default()
+        }
+    }
+
+    #[test]
+    fn parent_and_first_child() {
+        let mut state = rendered();
+        state.select(vec!["a", "d", "e"]);
+        assert!(state.select_parent());
+        assert_eq!(state.selected, ["a", "d"]);
+        assert!(state.select_first_child());
+        assert_eq!(state.selected, ["a", "d", "e"]);
+
+        state.select(vec!["a", "b"]);
+        assert!(!state.select_first_child());
+        state.select(vec!["f"]);
+        assert!(!state.select_parent());
+    }
+
+    #[test]
+    fn siblings() {
+        let mut state = rendered();
+        state.select(vec!["a", "b"]);
+        assert!(state.select_next_sibling());
+        assert_eq!(state.selected, ["a", "c"]);
+        assert!(state.select_next_sibling());
+        assert_eq!(state.selected, ["a", "d"]);
+        assert!(!state.select_next_sibling());
+        assert!(state.select_prev_sibling());
+        assert_eq!(state.selected, ["a", "c"]);
+
+        state.select(vec!["a"]);
+        assert!(state.select_next_sibling());
+        assert_eq!(state.selected, ["f"]);
+        assert!(state.select_prev_sibling());
+        assert_eq!(state.selected, ["a"]);
+    }
+
+    #[test]
+    fn from_empty_selection() {
+        let mut state = rendered();
+        assert_eq!(state.selected_position(), None);
+        assert!(!state.toggle_selected());
+        assert!(!state.select_first_child());
+        assert!(state.select_next());
+        assert_eq!(state.selected, ["a"]);
+
+        state.select(Vec::new());
+        assert!(state.select_prev());
+        assert_eq!(state.selected, ["f"]);
+
+        state.select(Vec::new());
+        assert!(state.select_next_directory());
+        assert_eq!(state.selected, ["a"]);
+    }
+
+    #[test]
+    fn next_directory() {
+        let mut state = rendered();
+        state.select(vec!["a", "c"]);
+        assert!(state.select_next_directory());
+        assert_eq!(state.selected, ["a", "d"]);
+        assert!(!state.select_next_directory());
+    }
+
+    #[test]
+    fn pages() {
+        let mut state = rendered();
+        state.select(vec!["a"]);
+        assert!(state.page_down());
+        assert_eq!(state.selected, ["a", "d", "e"]);
+        assert!(state.page_down());
+        assert_eq!(state.selected, ["f"]);
+        assert!(!state.page_down());
+        assert!(state.half_page_up());
+        assert_eq!(state.selected, ["a", "d"]);
+        assert!(state.page_up());
+        assert_eq!(state.selected, ["a"]);
+    }
+
+    #[test]
+    fn arrow_keys() {
+        let mut state = rendered();
+        state.select(vec!["a", "d"]);
+        assert!(state.collapse_or_select_parent());
+        assert_eq!(state.selected, ["a", "d"]);
+        assert!(!state.expanded.contains(&vec!["a", "d"]));
+        assert!(state.collapse_or_select_parent());
+        assert_eq!(state.selected, ["a"]);
+
+        state.select(vec!["a", "b"]);
+        assert!(state.expand_or_select_first_child());
+        assert!(state.expanded.contains(&vec!["a", "b"]));
+        state.select(vec!["a", "c"]);
+        assert!(!state.expand_or_select_first_child());
+    }
+
+    /// ```text
+    /// a
+    /// ├ b
+    /// │ └ x
+    /// └ c
+    /// d
+    /// └ e
+    ///   └ f
+    /// ```
+    fn items() -> Vec<TreeItem<'static, &'static str>> {
+        let leaf = |name| TreeItem::new_leaf(name, name);
+        let node = |name, children| TreeItem::new(name, name, children).unwrap();
+        vec![
+            node("a", vec![node("b", vec![leaf("x")]), leaf("c")]),
+            node("d", vec![node("e", vec![leaf("f")])]),
+        ]
+    }
+
+    fn expanded(state: &ExplorerState<&'static str>) -> Vec<String> {
+        let mut expanded = state
+            .expanded
+            .iter()
+            .map(|identifier| identifier.join("/"))
+            .collect::<Vec<_>>();
+        expanded.sort();
+        expanded
+    }
+
+    #[test]
+    fn expand_all_and_limit() {
+        let mut state = ExplorerState::default();
+        assert!(state.expand_all(&items(), 3).is_err());
+        assert!(state.expanded.is_empty());
+        assert!(state.expand_all(&items(), 4).unwrap());
+        assert_eq!(expanded(&state), ["a", "a/b", "d", "d/e"]);
+        assert!(!state.expand_all(&items(), DEFAULT_EXPAND_LIMIT).unwrap());
+    }
+
+    #[test]
+    fn expand_and_collapse_recursive() {
+        let mut state = ExplorerState::default();
+        assert!(
+            state
+                .expand_recursive(&items(), &["d"], Some(0), DEFAULT_EXPAND_LIMIT)
+                .unwrap()
+        );
+        assert_eq!(expanded(&state), ["d"]);
+        assert!(
+            state
+                .expand_recursive(&items(), &["a"], None, DEFAULT_EXPAND_LIMIT)
+                .unwrap()
+        );
+        assert_eq!(expanded(&state), ["a", "a/b", "d"]);
+        assert!(
+            state
+                .expand_recursive(&items(), &["missing"], None, DEFAULT_EXPAND_LIMIT)
+                .is_err()
+        );
+
+        assert!(state.collapse_recursive(&["a"]));
+        assert_eq!(expanded(&state), ["d"]);
+    }
+
+    #[test]
+    fn expand_to_level() {
+        let mut state = ExplorerState::default();
+        assert!(
+            state
+                .expand_to_level(&items(), 1, DEFAULT_EXPAND_LIMIT)
+                .unwrap()
+        );
+        assert_eq!(expanded(&state), ["a", "d"]);
+        assert!(
+            state
+                .expand_to_level(&items(), 2, DEFAULT_EXPAND_LIMIT)
+                .unwrap()
+        );
+        assert_eq!(expanded(&state), ["a", "a/b", "d", "d/e"]);
+        assert!(
+            state
+                .expand_to_level(&items(), 0, DEFAULT_EXPAND_LIMIT)
+                .unwrap()
+        );
+        assert!(state.expanded.is_empty());
+    }
+
+    #[test]
+    fn collapse_siblings() {
+        let mut state = ExplorerState::default();
+        state.expand_all(&items(), DEFAULT_EXPAND_LIMIT).unwrap();
+        state.select(vec!["a", "b"]);
+        assert!(!state.collapse_siblings());
+        state.select(vec!["a"]);
+        assert!(state.collapse_siblings());
+        assert_eq!(expanded(&state), ["a", "a/b"]);
+    }
+
+    #[test]
+    fn jump_back_and_forward() {
+        let mut state = ExplorerState::default();
+        state.select(vec!["a", "b", "x"]);
+        assert!(state.jump(vec!["d", "e"]));
+        state.select(vec!["d", "e", "f"]);
+        assert!(state.jump(vec!["a", "c"]));
+
+        assert!(state.back(&items()));
+        assert_eq!(state.selected, ["d", "e", "f"]);
+        assert!(state.back(&items()));
+        assert_eq!(state.selected, ["a", "b", "x"]);
+        assert!(state.expanded.contains(&vec!["a", "b"]));
+        assert!(!state.back(&items()));
+
+        assert!(state.forward(&items()));
+        assert_eq!(state.selected, ["d", "e", "f"]);
+        assert!(state.expanded.contains(&vec!["d", "e"]));
+    }
+
+    #[test]
+    fn back_skips_missing() {
+        let mut state = ExplorerState::default();
+        state.select(vec!["a", "c"]);
+        state.jump(vec!["gone"]);
+        state.jump(vec!["d"]);
+        assert!(state.back(&items()));
+        assert_eq!(state.selected, ["a", "c"]);
+    }
 }
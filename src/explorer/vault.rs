@@ -0,0 +1,302 @@
+use std::io::Read as _;
+use std::path::Path;
+
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::fs::FileSystem;
+
+/// Directories ignored in vault mode unless configured otherwise.
+pub const DEFAULT_IGNORED: [&str; 1] = [".obsidian"];
+
+/// Amount of bytes read from the start of a note to find its title.
+const TITLE_READ_LIMIT: u64 = 4096;
+
+/// Where the label of a note comes from.
+/// Falls back to the file name when the note has no such title.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteTitle {
+    FileName,
+    /// The first `# heading` of the note
+    Heading,
+    /// The `title` of the YAML front matter
+    FrontMatter,
+    /// The `title` of the front matter, otherwise the first `# heading`
+    #[default]
+    FrontMatterOrHeading,
+}
+
+/// Options of the vault mode of an [`Explorer`](crate::explorer::Explorer),
+/// for directories of Markdown notes like an Obsidian vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultOptions {
+    title: NoteTitle,
+    hide_extension: bool,
+    ignored: Vec<String>,
+    note_counts: bool,
+}
+
+impl Default for VaultOptions {
+    fn default() -> Self {
+        Self {
+            title: NoteTitle::default(),
+            hide_extension: true,
+            ignored: DEFAULT_IGNORED.iter().map(ToString::to_string).collect(),
+            note_counts: true,
+        }
+    }
+}
+
+impl VaultOptions {
+    /// Where the labels of notes come from. Defaults to [`NoteTitle::FrontMatterOrHeading`].
+    #[must_use]
+    pub const fn title(mut self, title: NoteTitle) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Hide the `.md` extension of notes labeled by their file name. Defaults to `true`.
+    #[must_use]
+    pub const fn hide_extension(mut self, hide_extension: bool) -> Self {
+        self.hide_extension = hide_extension;
+        self
+    }
+
+    /// Names of files and directories left out of the tree. Defaults to [`DEFAULT_IGNORED`].
+    #[must_use]
+    pub fn ignored<I>(mut self, ignored: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.ignored = ignored.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Show the amount of notes below each folder next to its name. Defaults to `true`.
+    #[must_use]
+    pub const fn note_counts(mut self, note_counts: bool) -> Self {
+        self.note_counts = note_counts;
+        self
+    }
+
+    /// Label of a directory with the amount of notes below it.
+    #[must_use]
+    pub fn directory_label(&self, name: &str, notes: usize) -> Line<'static> {
+        if self.note_counts {
+            Line::from(vec![
+                Span::raw(name.to_string()),
+                Span::styled(
+                    format!(" {notes}"),
+                    Style::new().add_modifier(Modifier::DIM),
+                ),
+            ])
+        } else {
+            Line::raw(name.to_string())
+        }
+    }
+
+    /// Whether the path is left out of the tree because it is or lies within an ignored name.
+    #[must_use]
+    pub fn is_ignored(&self, root: &Path, path: &Path) -> bool {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .any(|component| {
+                self.ignored
+                    .iter()
+                    .any(|ignored| component.as_os_str() == ignored.as_str())
+            })
+    }
+
+    /// Label of the given file: the title of a note or its file name.
+    #[must_use]
+    pub fn label(&self, fs: &dyn FileSystem, path: &Path, file_name: &str) -> String {
+        if !is_note(path) {
+            return file_name.to_string();
+        }
+        if let Some(title) = note_title(fs, path, self.title) {
+            return title;
+        }
+        if self.hide_extension {
+            if let Some(stem) = path.file_stem() {
+                return stem.to_string_lossy().to_string();
+            }
+        }
+        file_name.to_string()
+    }
+}
+
+/// Whether the path is a Markdown note.
+#[must_use]
+pub fn is_note(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("md"))
+}
+
+/// Read the title of a note from the start of the file.
+#[must_use]
+pub fn note_title(fs: &dyn FileSystem, path: &Path, source: NoteTitle) -> Option<String> {
+    if source == NoteTitle::FileName {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    fs.open(path)
+        .ok()?
+        .take(TITLE_READ_LIMIT)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    parse_title(&String::from_utf8_lossy(&bytes), source)
+}
+
+fn parse_title(text: &str, source: NoteTitle) -> Option<String> {
+    match source {
+        NoteTitle::FileName => None,
+        NoteTitle::Heading => first_heading(text),
+        NoteTitle::FrontMatter => front_matter_title(text),
+        NoteTitle::FrontMatterOrHeading => front_matter_title(text).or_else(|| first_heading(text)),
+    }
+}
+
+/// Lines of the YAML front matter and the lines after it.
+fn split_front_matter(text: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return (Vec::new(), text.lines().collect());
+    }
+    let mut front_matter = Vec::new();
+    for line in lines.by_ref() {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (front_matter, lines.collect());
+        }
+        front_matter.push(line);
+    }
+    // Not closed, so it is no front matter
+    (Vec::new(), text.lines().collect())
+}
+
+fn front_matter_title(text: &str) -> Option<String> {
+    let (front_matter, _) = split_front_matter(text);
+    front_matter.iter().find_map(|line| {
+        let value = line.strip_prefix("title:")?.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })
+            .unwrap_or(value);
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+fn first_heading(text: &str) -> Option<String> {
+    let (_, body) = split_front_matter(text);
+    let mut in_code_block = false;
+    body.iter().find_map(|line| {
+        let line = line.trim_start();
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            return None;
+        }
+        let heading = line.strip_prefix("# ")?.trim();
+        (!heading.is_empty()).then(|| heading.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidgetRef as _;
+
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::explorer::Explorer;
+    use crate::explorer::state::ExplorerState;
+    use crate::fs::memory::InMemoryFs;
+
+    const NOTE: &str = "---\ntags: [a]\ntitle: \"From front matter\"\n---\n\n```\n# not a heading\n```\n# From heading\n";
+
+    #[test]
+    fn titles() {
+        assert_eq!(
+            parse_title(NOTE, NoteTitle::FrontMatter).as_deref(),
+            Some("From front matter")
+        );
+        assert_eq!(
+            parse_title(NOTE, NoteTitle::Heading).as_deref(),
+            Some("From heading")
+        );
+        assert_eq!(
+            parse_title("# Only heading", NoteTitle::FrontMatterOrHeading).as_deref(),
+            Some("Only heading")
+        );
+        assert_eq!(
+            parse_title("---\ntitle: open", NoteTitle::FrontMatter),
+            None
+        );
+        assert_eq!(parse_title(NOTE, NoteTitle::FileName), None);
+    }
+
+    #[test]
+    fn ignored() {
+        let options = VaultOptions::default();
+        let root = Path::new("/vault");
+        assert!(options.is_ignored(root, Path::new("/vault/.obsidian")));
+        assert!(options.is_ignored(root, Path::new("/vault/.obsidian/app.json")));
+        assert!(!options.is_ignored(root, Path::new("/vault/notes/obsidian.md")));
+    }
+
+    #[test]
+    fn explorer_in_vault_mode() {
+        let root = PathBuf::from("/vault");
+        let file_system = InMemoryFs::new()
+            .with_file("/vault/.obsidian/app.json", "{}")
+            .with_file("/vault/notes/alpha.md", "# Alpha\n")
+            .with_file("/vault/notes/beta.md", "no title")
+            .with_file("/vault/todo.txt", "");
+
+        let mut explorer = Explorer::new("vault", &root)
+            .unwrap()
+            .file_system(file_system)
+            .vault_mode(VaultOptions::default());
+        explorer
+            .add_entries([
+                root.join(".obsidian"),
+                root.join(".obsidian/app.json"),
+                root.join("notes"),
+                root.join("notes/alpha.md"),
+                root.join("notes/beta.md"),
+                root.join("todo.txt"),
+            ])
+            .unwrap();
+
+        let mut state = ExplorerState::default();
+        state.expand(vec![root.join("notes")]);
+        let area = Rect::new(0, 0, 16, 4);
+        let mut buffer = Buffer::empty(area);
+        explorer.tree.render_ref(area, &mut buffer, &mut state);
+        let rows = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                "▼ notes 2       ",
+                "    Alpha       ",
+                "    beta        ",
+                "  todo.txt      ",
+            ]
+        );
+    }
+}
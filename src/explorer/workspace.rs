@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+
+use crate::fs::PathLike;
+
+/// One of the root folders of an [`Explorer`](crate::explorer::Explorer) in workspace mode,
+/// shown as a collapsible header above its entries.
+///
+/// Its node is the first element of the identifiers of all nodes below it,
+/// which tells the roots apart in an [`ExplorerState`](crate::explorer::state::ExplorerState).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRoot<T> {
+    pub path: T,
+    /// Text of the header, the name of the folder by default
+    pub label: String,
+    /// Names of files and directories below this root which are left out of the tree
+    pub ignored: Vec<String>,
+    pub header_style: Style,
+}
+
+impl<T> WorkspaceRoot<T>
+where
+    T: PathLike,
+{
+    /// Create a new `WorkspaceRoot` labeled by the name of its folder.
+    #[must_use]
+    pub fn new(path: T) -> Self {
+        let label = path.as_ref().file_name().map_or_else(
+            || path.as_ref().display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        Self {
+            path,
+            label,
+            ignored: Vec::new(),
+            header_style: Style::new().add_modifier(Modifier::BOLD),
+        }
+    }
+
+    #[must_use]
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+
+    /// Names of files and directories left out of the tree, like `target` or `.git`.
+    #[must_use]
+    pub fn ignored<I>(mut self, ignored: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.ignored = ignored.into_iter().map(Into::into).collect();
+        self
+    }
+
+    #[must_use]
+    pub const fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// Whether the path lies below this root.
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(self.path.as_ref())
+    }
+
+    /// Whether the path is left out of the tree because it is or lies within an ignored name.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        path.strip_prefix(self.path.as_ref())
+            .unwrap_or(path)
+            .components()
+            .any(|component| {
+                self.ignored
+                    .iter()
+                    .any(|ignored| component.as_os_str() == ignored.as_str())
+            })
+    }
+
+    /// Text of the header of this root.
+    #[must_use]
+    pub fn header(&self) -> Line<'static> {
+        Line::styled(self.label.clone(), self.header_style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::explorer::Explorer;
+    use crate::explorer::state::ExplorerState;
+
+    fn explorer(root: &PathBuf) -> Explorer<'_, PathBuf> {
+        let mut explorer = Explorer::new("workspace", root).unwrap();
+        explorer
+            .add_entries([
+                PathBuf::from("/ki-one/a.rs"),
+                PathBuf::from("/ki-one/target"),
+                PathBuf::from("/ki-two/a.rs"),
+            ])
+            .unwrap();
+        explorer
+            .add_root(WorkspaceRoot::new(PathBuf::from("/ki-one")).ignored(["target"]))
+            .unwrap();
+        explorer
+            .add_root(WorkspaceRoot::new(PathBuf::from("/ki-two")).label("Two"))
+            .unwrap();
+        explorer
+    }
+
+    #[test]
+    fn roots_as_headers() {
+        let root = PathBuf::from("/ki-one");
+        let explorer = explorer(&root);
+
+        let items = explorer.tree.items();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].identifier(), Path::new("/ki-one"));
+        assert_eq!(items[0].children().len(), 1);
+        assert_eq!(items[1].identifier(), Path::new("/ki-two"));
+        assert_eq!(items[1].children().len(), 1);
+    }
+
+    #[test]
+    fn identifiers_and_reveal_span_roots() {
+        let root = PathBuf::from("/ki-one");
+        let mut explorer = explorer(&root);
+
+        assert_eq!(
+            explorer.identifier(Path::new("/ki-two/a.rs")),
+            Some(vec![
+                PathBuf::from("/ki-two"),
+                PathBuf::from("/ki-two/a.rs")
+            ])
+        );
+        assert_eq!(
+            explorer.identifier(Path::new("a.rs")),
+            Some(vec![
+                PathBuf::from("/ki-one"),
+                PathBuf::from("/ki-one/a.rs")
+            ])
+        );
+        assert_eq!(explorer.identifier(Path::new("/elsewhere/a.rs")), None);
+
+        let mut state = ExplorerState::default();
+        explorer
+            .reveal(&mut state, Path::new("/ki-two/a.rs"))
+            .unwrap();
+        assert_eq!(
+            state.selected,
+            [PathBuf::from("/ki-two"), PathBuf::from("/ki-two/a.rs")]
+        );
+        assert!(state.expanded.contains(&vec![PathBuf::from("/ki-two")]));
+        let missing = explorer.reveal(&mut state, Path::new("/ki-two/b.rs"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        assert!(explorer.remove_root(Path::new("/ki-two")).unwrap());
+        assert!(!explorer.entries.contains(Path::new("/ki-two/a.rs")));
+        assert_eq!(explorer.tree.items().len(), 1);
+        assert!(!explorer.remove_root(Path::new("/ki-two")).unwrap());
+    }
+}
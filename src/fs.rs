@@ -1,45 +1,52 @@
-use std::{cmp::Ordering, fmt::Debug, fs, hash::Hash, path::Path, path::PathBuf};
+use core::fmt::Debug;
+use core::hash::Hash;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
 
-/// TODO
-#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod memory;
+#[cfg(feature = "process")]
+pub mod process;
+
+/// A path ordered by its components, without touching any [`FileSystem`].
+/// The explorer puts directories first when it builds the tree.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct SortablePath(pub PathBuf);
 
 impl SortablePath {
+    #[deprecated(note = "ask a `FileSystem` with `FileSystem::is_dir` instead")]
+    #[must_use]
     pub fn is_dir(&self) -> bool {
-        fs::metadata(&self.0).map(|m| m.is_dir()).unwrap_or(false)
-    }
-}
-
-impl Ord for SortablePath {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self.is_dir(), other.is_dir()) {
-            // If both are directories or both are files, compare alphabetically
-            (true, true) | (false, false) => self.0.cmp(&other.0),
-            // Directories come before files
-            (true, false) => Ordering::Less,
-            (false, true) => Ordering::Greater,
-        }
-    }
-}
-
-impl PartialOrd for SortablePath {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+        StdFs.is_dir(&self.0)
     }
 }
 
 pub trait PathLike: AsRef<Path> + Clone + Eq + PartialEq + Ord + Hash + Debug {
-    fn is_dir(&self) -> bool;
+    /// Whether the path is a directory on the local disk.
+    #[deprecated(note = "ask a `FileSystem` with `FileSystem::is_dir` instead")]
+    fn is_dir(&self) -> bool {
+        StdFs.is_dir(self.as_ref())
+    }
+    #[must_use]
     fn join<P: AsRef<Path>>(&self, path: P) -> Self;
+    /// The parent directory, `None` for a root or an empty path.
+    fn parent(&self) -> Option<Self>;
 }
 
 impl PathLike for SortablePath {
-    fn is_dir(&self) -> bool {
-        fs::metadata(&self.0).map(|m| m.is_dir()).unwrap_or(false)
+    fn join<P: AsRef<Path>>(&self, path: P) -> Self {
+        Self(self.0.join(path))
     }
 
-    fn join<P: AsRef<Path>>(&self, path: P) -> Self {
-        SortablePath(self.0.join(path))
+    fn parent(&self) -> Option<Self> {
+        self.0
+            .as_path()
+            .parent()
+            .map(|parent| Self(parent.to_path_buf()))
     }
 }
 
@@ -50,17 +57,210 @@ impl AsRef<Path> for SortablePath {
 }
 
 impl PathLike for PathBuf {
-    fn is_dir(&self) -> bool {
-        self.as_path().is_dir()
-    }
-
     fn join<P: AsRef<Path>>(&self, path: P) -> Self {
         self.as_path().join(path)
     }
+
+    fn parent(&self) -> Option<Self> {
+        self.as_path().parent().map(Path::to_path_buf)
+    }
 }
 
 impl From<PathBuf> for SortablePath {
     fn from(path: PathBuf) -> Self {
-        SortablePath(path)
+        Self(path)
+    }
+}
+
+/// What a [`FileSystem`] knows about a file or directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub is_dir: bool,
+    /// Size of a file in bytes, `0` for directories
+    pub len: u64,
+    /// Time of the last modification when the file system keeps it
+    pub modified: Option<SystemTime>,
+}
+
+impl Metadata {
+    #[must_use]
+    pub const fn is_file(&self) -> bool {
+        !self.is_dir
+    }
+}
+
+/// The file operations of the explorer, so it can browse more than the local disk
+/// and be tested without real files.
+///
+/// See [`StdFs`] for the local disk and [`InMemoryFs`](memory::InMemoryFs) for tests and demos.
+pub trait FileSystem: Debug {
+    /// Paths of the entries of a directory, in no particular order.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path is no directory or can not be read.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// # Errors
+    ///
+    /// Errors when the path does not exist.
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    /// Like [`metadata`](Self::metadata) but of a symbolic link itself instead of its target,
+    /// so a broken link can be told apart from a missing path.
+    /// File systems without links answer like [`metadata`](Self::metadata).
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path does not exist.
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.metadata(path)
+    }
+
+    /// Open a file for reading.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path is no file or can not be read.
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>>;
+
+    /// Open a file for reading at most `limit` bytes, like for a preview.
+    /// Remote file systems only transfer that much.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path is no file or can not be read.
+    fn open_limited(&self, path: &Path, limit: u64) -> io::Result<Box<dyn Read>> {
+        Ok(Box::new(self.open(path)?.take(limit)))
+    }
+
+    /// Rename or move a file or directory.
+    ///
+    /// # Errors
+    ///
+    /// Errors when `from` does not exist or `to` can not be created.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Remove a file, or a directory with all of its contents.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path does not exist or can not be removed.
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Create a directory and its missing parents.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the directory can not be created.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Create or replace a file.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the parent directory does not exist or the file can not be written.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|metadata| metadata.is_dir)
+    }
+}
+
+/// Lets an explorer and a preview share one file system, like `explorer.fs.clone()`.
+impl<F> FileSystem for Rc<F>
+where
+    F: FileSystem + ?Sized,
+{
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        (**self).read_dir(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        (**self).metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        (**self).symlink_metadata(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        (**self).open(path)
+    }
+
+    fn open_limited(&self, path: &Path, limit: u64) -> io::Result<Box<dyn Read>> {
+        (**self).open_limited(path, limit)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        (**self).rename(from, to)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        (**self).remove(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        (**self).create_dir(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        (**self).write(path, contents)
+    }
+}
+
+/// The local disk, through [`std::fs`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StdFs;
+
+impl FileSystem for StdFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::metadata(path).map(|metadata| convert(&metadata))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::symlink_metadata(path).map(|metadata| convert(&metadata))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        if fs::symlink_metadata(path)?.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+}
+
+fn convert(metadata: &fs::Metadata) -> Metadata {
+    Metadata {
+        is_dir: metadata.is_dir(),
+        len: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata.modified().ok(),
     }
 }
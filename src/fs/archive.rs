@@ -0,0 +1,554 @@
+use core::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use flate2::read::GzDecoder;
+
+use crate::fs::{FileSystem, Metadata, StdFs};
+
+/// The kinds of archives an [`ArchiveFs`] looks into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    /// A tar archive compressed with gzip, `.tar.gz` or `.tgz`
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Detect the kind of archive by the file name.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = PathBuf::from(path.file_name()?.to_ascii_lowercase());
+        match name.extension()?.to_str()? {
+            "tgz" => Some(Self::TarGz),
+            "gz" if Path::new(name.file_stem()?).extension() == Some("tar".as_ref()) => {
+                Some(Self::TarGz)
+            }
+            "tar" => Some(Self::Tar),
+            "zip" => Some(Self::Zip),
+            _ => None,
+        }
+    }
+}
+
+/// An entry of an archive as listed in its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Directory,
+    File { len: u64 },
+}
+
+/// The entries of an archive by their path, the archive itself being the top directory.
+type Index = BTreeMap<PathBuf, Entry>;
+
+#[derive(Debug)]
+struct LoadedArchive {
+    modified: Option<SystemTime>,
+    index: Rc<Index>,
+}
+
+/// A [`FileSystem`] which shows `.zip`, `.tar` and `.tar.gz` files of another file system
+/// as read only directories, like `release.tar.gz/bin/ki`.
+///
+/// The names of the entries of an archive are read when it is first looked into,
+/// and again when it changed. A file is only unpacked when it is opened.
+/// Load their entries like the ones of a directory with
+/// [`Explorer::load_dir`](crate::explorer::Explorer::load_dir).
+/// To [`symlink_metadata`](FileSystem::symlink_metadata) an archive is a file,
+/// so a [`Scan`](crate::explorer::scan::Scan) does not look into every archive below its roots.
+/// Archives within archives are shown as files.
+#[derive(Debug, Default)]
+pub struct ArchiveFs<F = StdFs> {
+    inner: F,
+    archives: RefCell<HashMap<PathBuf, LoadedArchive>>,
+}
+
+impl<F> ArchiveFs<F>
+where
+    F: FileSystem,
+{
+    /// Look into the archives of the given file system.
+    #[must_use]
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            archives: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The archive the path is or lies within and its kind.
+    fn archive_of(&self, path: &Path) -> Option<(PathBuf, ArchiveKind)> {
+        let mut ancestors = path.ancestors().collect::<Vec<_>>();
+        ancestors.reverse();
+        ancestors.into_iter().find_map(|ancestor| {
+            let kind = ArchiveKind::from_path(ancestor)?;
+            let metadata = self.inner.metadata(ancestor).ok()?;
+            metadata.is_file().then(|| (ancestor.to_path_buf(), kind))
+        })
+    }
+
+    /// The index of the archive at the given path, read again when its modification time changed.
+    /// Without modification times archives are only read again after they were changed through this.
+    fn index(&self, archive: &Path, kind: ArchiveKind) -> io::Result<Rc<Index>> {
+        let modified = self.inner.metadata(archive)?.modified;
+        if let Some(loaded) = self.archives.borrow().get(archive) {
+            if loaded.modified == modified {
+                return Ok(Rc::clone(&loaded.index));
+            }
+        }
+
+        let index = Rc::new(read_index(archive, kind, self.bytes(archive)?)?);
+        self.archives.borrow_mut().insert(
+            archive.to_path_buf(),
+            LoadedArchive {
+                modified,
+                index: Rc::clone(&index),
+            },
+        );
+        Ok(index)
+    }
+
+    /// The entry at the path within the archive.
+    fn entry(&self, archive: &Path, kind: ArchiveKind, path: &Path) -> io::Result<Entry> {
+        self.index(archive, kind)?
+            .get(path)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} does not exist", path.display()),
+                )
+            })
+    }
+
+    fn bytes(&self, archive: &Path) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.inner.open(archive)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Fails when the path lies within an archive, which can not be changed.
+    fn ensure_writable(&self, path: &Path) -> io::Result<()> {
+        match self.archive_of(path) {
+            Some((archive, _)) if archive != path => Err(io::Error::new(
+                io::ErrorKind::ReadOnlyFilesystem,
+                format!("{} lies within an archive", path.display()),
+            )),
+            Some((archive, _)) => {
+                self.archives.borrow_mut().remove(&archive);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Copy the file or directory at `path`, which may lie within an archive,
+    /// into the `destination` directory of the inner file system.
+    ///
+    /// Returns the path of the copy.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the path can not be read or the copy can not be written.
+    pub fn extract(&self, path: &Path, destination: &Path) -> io::Result<PathBuf> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?;
+        let target = destination.join(name);
+        self.copy(path, &target)?;
+        Ok(target)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        if self.is_dir(from) {
+            self.inner.create_dir(to)?;
+            for child in self.read_dir(from)? {
+                if let Some(name) = child.file_name() {
+                    self.copy(&child, &to.join(name))?;
+                }
+            }
+            Ok(())
+        } else {
+            let mut bytes = Vec::new();
+            self.open(from)?.read_to_end(&mut bytes)?;
+            self.inner.write(to, &bytes)
+        }
+    }
+}
+
+impl<F> FileSystem for ArchiveFs<F>
+where
+    F: FileSystem,
+{
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let Some((archive, kind)) = self.archive_of(path) else {
+            return self.inner.read_dir(path);
+        };
+        let index = self.index(&archive, kind)?;
+        if index.get(path) != Some(&Entry::Directory) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("{} is not a directory", path.display()),
+            ));
+        }
+        // Entries below a path follow it in the order of paths
+        Ok(index
+            .range(path.to_path_buf()..)
+            .map(|(child, _)| child)
+            .take_while(|child| child.starts_with(path))
+            .filter(|child| child.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        match self.archive_of(path) {
+            // The archive itself is a directory of its entries
+            Some((archive, _)) if archive == path => Ok(Metadata {
+                is_dir: true,
+                ..self.inner.metadata(path)?
+            }),
+            Some((archive, kind)) => {
+                let (is_dir, len) = match self.entry(&archive, kind, path)? {
+                    Entry::Directory => (true, 0),
+                    Entry::File { len } => (false, len),
+                };
+                Ok(Metadata {
+                    is_dir,
+                    len,
+                    modified: None,
+                })
+            }
+            None => self.inner.metadata(path),
+        }
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        // Archives have no links within them
+        match self.archive_of(path) {
+            Some((archive, _)) if archive != path => self.metadata(path),
+            _ => self.inner.symlink_metadata(path),
+        }
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        match self.archive_of(path) {
+            Some((archive, kind)) if archive != path => {
+                if self.entry(&archive, kind, path)? == Entry::Directory {
+                    return Err(io::Error::new(
+                        io::ErrorKind::IsADirectory,
+                        format!("{} is a directory", path.display()),
+                    ));
+                }
+                let contents = read_file(&archive, kind, self.bytes(&archive)?, path)?;
+                Ok(Box::new(Cursor::new(contents)))
+            }
+            _ => self.inner.open(path),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.ensure_writable(from)?;
+        self.ensure_writable(to)?;
+        self.inner.rename(from, to)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.ensure_writable(path)?;
+        self.inner.remove(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.ensure_writable(path)?;
+        self.inner.create_dir(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.ensure_writable(path)?;
+        self.inner.write(path, contents)
+    }
+}
+
+/// Read the names of the entries of an archive, below the path of the archive.
+/// Their contents are skipped.
+fn read_index(archive: &Path, kind: ArchiveKind, bytes: Vec<u8>) -> io::Result<Index> {
+    let mut index = Index::new();
+    index.insert(archive.to_path_buf(), Entry::Directory);
+    let mut add = |path: PathBuf, entry| {
+        for ancestor in path.ancestors().skip(1) {
+            if !ancestor.starts_with(archive) {
+                break;
+            }
+            index
+                .entry(ancestor.to_path_buf())
+                .or_insert(Entry::Directory);
+        }
+        index.insert(path, entry);
+    };
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(io::Error::other)?;
+            for position in 0..zip.len() {
+                let file = zip.by_index_raw(position).map_err(io::Error::other)?;
+                if let Some(path) = zip_path(archive, &file) {
+                    let entry = if file.is_dir() {
+                        Entry::Directory
+                    } else {
+                        Entry::File { len: file.size() }
+                    };
+                    add(path, entry);
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = tar::Archive::new(tar_reader(kind, bytes));
+            for entry in tar.entries()? {
+                let entry = entry?;
+                let Some(path) = tar_path(archive, &entry.path()?) else {
+                    continue;
+                };
+                let entry_type = entry.header().entry_type();
+                if entry_type.is_dir() {
+                    add(path, Entry::Directory);
+                } else if entry_type.is_file() {
+                    add(path, Entry::File { len: entry.size() });
+                }
+                // Links and other special entries are left out
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Unpack the contents of the file at the path within the archive.
+fn read_file(
+    archive: &Path,
+    kind: ArchiveKind,
+    bytes: Vec<u8>,
+    path: &Path,
+) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(io::Error::other)?;
+            for position in 0..zip.len() {
+                let file = zip.by_index_raw(position).map_err(io::Error::other)?;
+                if zip_path(archive, &file).as_deref() == Some(path) && file.is_file() {
+                    drop(file);
+                    zip.by_index(position)
+                        .map_err(io::Error::other)?
+                        .read_to_end(&mut contents)?;
+                    return Ok(contents);
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = tar::Archive::new(tar_reader(kind, bytes));
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                if tar_path(archive, &entry.path()?).as_deref() == Some(path)
+                    && entry.header().entry_type().is_file()
+                {
+                    entry.read_to_end(&mut contents)?;
+                    return Ok(contents);
+                }
+            }
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} does not exist", path.display()),
+    ))
+}
+
+/// Path of a zip entry below the archive.
+/// Entries escaping the archive like `../evil` are left out.
+fn zip_path(archive: &Path, file: &zip::read::ZipFile<'_>) -> Option<PathBuf> {
+    file.enclosed_name().map(|name| archive.join(name))
+}
+
+/// Path of a tar entry below the archive.
+/// Entries escaping the archive like `../evil` are left out.
+fn tar_path(archive: &Path, name: &Path) -> Option<PathBuf> {
+    if name
+        .components()
+        .any(|component| component == Component::ParentDir)
+    {
+        return None;
+    }
+    let mut path = archive.to_path_buf();
+    for component in name.components() {
+        if let Component::Normal(name) = component {
+            path.push(name);
+        }
+    }
+    (path != archive).then_some(path)
+}
+
+fn tar_reader(kind: ArchiveKind, bytes: Vec<u8>) -> Box<dyn Read> {
+    if kind == ArchiveKind::TarGz {
+        Box::new(GzDecoder::new(Cursor::new(bytes)))
+    } else {
+        Box::new(Cursor::new(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    use super::*;
+    use crate::fs::memory::InMemoryFs;
+
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in [("bin/ki", "binary"), ("README.md", "# ki")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn zip_bytes() -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_directory("docs/", options).unwrap();
+        writer.start_file("docs/guide.md", options).unwrap();
+        writer.write_all(b"# Guide").unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn file_system() -> ArchiveFs<InMemoryFs> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tar_bytes()).unwrap();
+        let inner = InMemoryFs::new()
+            .with_file("/downloads/release.tar", tar_bytes())
+            .with_file("/downloads/release.tgz", encoder.finish().unwrap())
+            .with_file("/downloads/export.zip", zip_bytes())
+            .with_file("/downloads/notes.txt", "plain");
+        ArchiveFs::new(inner)
+    }
+
+    fn read(file_system: &ArchiveFs<InMemoryFs>, path: &str) -> String {
+        let mut contents = String::new();
+        file_system
+            .open(Path::new(path))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn archives_as_directories() {
+        let file_system = file_system();
+        assert!(file_system.is_dir(Path::new("/downloads/release.tar")));
+        assert!(!file_system.is_dir(Path::new("/downloads/notes.txt")));
+
+        for archive in ["/downloads/release.tar", "/downloads/release.tgz"] {
+            let mut entries = file_system.read_dir(Path::new(archive)).unwrap();
+            entries.sort();
+            assert_eq!(
+                entries,
+                [
+                    Path::new(archive).join("README.md"),
+                    Path::new(archive).join("bin")
+                ]
+            );
+            assert_eq!(read(&file_system, &format!("{archive}/bin/ki")), "binary");
+        }
+
+        assert!(file_system.is_dir(Path::new("/downloads/export.zip/docs")));
+        assert_eq!(
+            read(&file_system, "/downloads/export.zip/docs/guide.md"),
+            "# Guide"
+        );
+        assert_eq!(read(&file_system, "/downloads/notes.txt"), "plain");
+    }
+
+    #[test]
+    fn files_are_unpacked_when_opened() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.start_file("guide.md", options).unwrap();
+        writer.write_all(b"# Guide").unwrap();
+        let mut bytes = writer.finish().unwrap().into_inner();
+        // Break the contents, the checksum no longer matches
+        let start = bytes
+            .windows(7)
+            .position(|window| window == b"# Guide")
+            .unwrap();
+        bytes[start + 2] = b'X';
+
+        let file_system = ArchiveFs::new(InMemoryFs::new().with_file("/export.zip", bytes));
+        let guide = Path::new("/export.zip/guide.md");
+        assert_eq!(
+            file_system.read_dir(Path::new("/export.zip")).unwrap(),
+            [guide]
+        );
+        assert_eq!(file_system.metadata(guide).unwrap().len, 7);
+        assert!(file_system.open(guide).is_err());
+
+        // A scan does not descend into the archive
+        assert!(file_system.is_dir(Path::new("/export.zip")));
+        assert!(
+            !file_system
+                .symlink_metadata(Path::new("/export.zip"))
+                .unwrap()
+                .is_dir
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_outside_archives() {
+        use crate::explorer::error::ExplorerError;
+        use crate::temp_dir::TempDir;
+
+        let dir = TempDir::new("archive-links");
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        std::os::unix::fs::symlink("..", dir.join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink("missing", dir.join("broken")).unwrap();
+
+        let file_system = ArchiveFs::new(StdFs);
+        let link = dir.join("sub/loop");
+        assert!(file_system.metadata(&link).unwrap().is_dir);
+        assert!(!file_system.symlink_metadata(&link).unwrap().is_dir);
+
+        let broken = dir.join("broken");
+        let error = file_system.metadata(&broken).unwrap_err();
+        assert_eq!(
+            ExplorerError::metadata(&file_system, &broken, &error),
+            ExplorerError::BrokenSymlink(broken)
+        );
+    }
+
+    #[test]
+    fn read_only_and_extract() {
+        let file_system = file_system();
+        let write = file_system.write(Path::new("/downloads/export.zip/new.md"), b"");
+        assert_eq!(write.unwrap_err().kind(), io::ErrorKind::ReadOnlyFilesystem);
+
+        let extracted = file_system
+            .extract(
+                Path::new("/downloads/release.tar/bin"),
+                Path::new("/downloads"),
+            )
+            .unwrap();
+        assert_eq!(extracted, Path::new("/downloads/bin"));
+        assert_eq!(read(&file_system, "/downloads/bin/ki"), "binary");
+    }
+}
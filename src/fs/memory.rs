@@ -0,0 +1,284 @@
+use core::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use crate::fs::{FileSystem, Metadata};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Directory,
+    File(Vec<u8>),
+}
+
+/// A [`FileSystem`] kept in memory, for tests and demos.
+///
+/// Paths are taken as they are, use absolute paths like `/project/src/main.rs`.
+/// Renaming onto an existing path replaces it only when both are files.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InMemoryFs {
+    nodes: RefCell<BTreeMap<PathBuf, Node>>,
+}
+
+impl InMemoryFs {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory and its missing parents.
+    #[must_use]
+    pub fn with_dir<P>(self, path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.insert_parents(path.as_ref());
+        self.nodes
+            .borrow_mut()
+            .insert(path.as_ref().to_path_buf(), Node::Directory);
+        self
+    }
+
+    /// Add a file and its missing parent directories.
+    #[must_use]
+    pub fn with_file<P, C>(self, path: P, contents: C) -> Self
+    where
+        P: AsRef<Path>,
+        C: Into<Vec<u8>>,
+    {
+        self.insert_parents(path.as_ref());
+        self.nodes
+            .borrow_mut()
+            .insert(path.as_ref().to_path_buf(), Node::File(contents.into()));
+        self
+    }
+
+    fn insert_parents(&self, path: &Path) {
+        let mut nodes = self.nodes.borrow_mut();
+        for ancestor in path.ancestors().skip(1) {
+            if !ancestor.as_os_str().is_empty() {
+                nodes
+                    .entry(ancestor.to_path_buf())
+                    .or_insert(Node::Directory);
+            }
+        }
+    }
+
+    fn node(&self, path: &Path) -> io::Result<Node> {
+        self.nodes
+            .borrow()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| not_found(path))
+    }
+
+    /// Fails unless the parent of the path is a directory.
+    fn ensure_parent(&self, path: &Path) -> io::Result<()> {
+        match path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            Some(parent) if self.node(parent)? != Node::Directory => Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("{} is not a directory", parent.display()),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FileSystem for InMemoryFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        if self.node(path)? != Node::Directory {
+            return Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("{} is not a directory", path.display()),
+            ));
+        }
+        Ok(self
+            .nodes
+            .borrow()
+            .keys()
+            .filter(|child| child.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let (is_dir, len) = match self.node(path)? {
+            Node::Directory => (true, 0),
+            Node::File(contents) => (false, contents.len() as u64),
+        };
+        Ok(Metadata {
+            is_dir,
+            len,
+            modified: None,
+        })
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        match self.node(path)? {
+            Node::Directory => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("{} is a directory", path.display()),
+            )),
+            Node::File(contents) => Ok(Box::new(Cursor::new(contents))),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let node = self.node(from)?;
+        self.ensure_parent(to)?;
+        if to.starts_with(from) && to != from {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Can not move {} into itself", from.display()),
+            ));
+        }
+        match self.nodes.borrow().get(to) {
+            Some(Node::File(_)) if matches!(node, Node::File(_)) => {}
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", to.display()),
+                ));
+            }
+            None => {}
+        }
+
+        let mut nodes = self.nodes.borrow_mut();
+        let moved = nodes
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in moved {
+            if let Some(node) = nodes.remove(&path) {
+                let relative = path.strip_prefix(from).unwrap_or(&path);
+                let target = if relative.as_os_str().is_empty() {
+                    to.to_path_buf()
+                } else {
+                    to.join(relative)
+                };
+                nodes.insert(target, node);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.node(path)?;
+        self.nodes
+            .borrow_mut()
+            .retain(|other, _| !other.starts_with(path));
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        for ancestor in path.ancestors() {
+            if let Ok(Node::File(_)) = self.node(ancestor) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotADirectory,
+                    format!("{} is a file", ancestor.display()),
+                ));
+            }
+        }
+        self.insert_parents(path);
+        self.nodes
+            .borrow_mut()
+            .insert(path.to_path_buf(), Node::Directory);
+        Ok(())
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.ensure_parent(path)?;
+        let mut nodes = self.nodes.borrow_mut();
+        if nodes.get(path) == Some(&Node::Directory) {
+            return Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("{} is a directory", path.display()),
+            ));
+        }
+        nodes.insert(path.to_path_buf(), Node::File(contents.to_vec()));
+        Ok(())
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} does not exist", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_system() -> InMemoryFs {
+        InMemoryFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/README.md", "# Project")
+            .with_dir("/project/target")
+    }
+
+    #[test]
+    fn read_and_metadata() {
+        let file_system = file_system();
+        let mut children = file_system.read_dir(Path::new("/project")).unwrap();
+        children.sort();
+        assert_eq!(
+            children,
+            [
+                PathBuf::from("/project/README.md"),
+                PathBuf::from("/project/src"),
+                PathBuf::from("/project/target"),
+            ]
+        );
+        assert!(file_system.is_dir(Path::new("/project/src")));
+        assert_eq!(
+            file_system
+                .metadata(Path::new("/project/README.md"))
+                .unwrap()
+                .len,
+            9
+        );
+
+        let mut contents = String::new();
+        file_system
+            .open(Path::new("/project/src/main.rs"))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "fn main() {}");
+
+        let missing = file_system.metadata(Path::new("/project/missing"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+        let file = file_system.read_dir(Path::new("/project/README.md"));
+        assert_eq!(file.unwrap_err().kind(), io::ErrorKind::NotADirectory);
+    }
+
+    #[test]
+    fn change() {
+        let file_system = file_system();
+        file_system
+            .rename(Path::new("/project/src"), Path::new("/project/lib"))
+            .unwrap();
+        assert!(file_system.exists(Path::new("/project/lib/main.rs")));
+        assert!(!file_system.exists(Path::new("/project/src")));
+
+        let taken = file_system.rename(Path::new("/project/lib"), Path::new("/project/target"));
+        assert_eq!(taken.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+
+        file_system.remove(Path::new("/project/lib")).unwrap();
+        assert!(!file_system.exists(Path::new("/project/lib/main.rs")));
+
+        file_system.create_dir(Path::new("/project/a/b")).unwrap();
+        assert!(file_system.is_dir(Path::new("/project/a")));
+        file_system
+            .write(Path::new("/project/a/b/c.rs"), b"")
+            .unwrap();
+        let orphan = file_system.write(Path::new("/project/x/y.rs"), b"");
+        assert_eq!(orphan.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,444 @@
+use core::cell::{Cell, RefCell};
+use core::time::Duration;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead as _, BufReader, Cursor, Read, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Instant;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::fs::{FileSystem, Metadata};
+
+/// How long a [`ProcessFs`] waits for a response by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Amount of replies kept for [`ProcessFs::poll`] at most, the oldest are dropped beyond.
+pub const MAX_RECEIVED: usize = 1024;
+
+/// What a [`Request`] asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// Names of the entries of a directory, answered by [`Reply::Entries`]
+    List,
+    /// Kind and size of a path, answered by [`Reply::Stat`]
+    Stat,
+    /// Contents of a file, answered by [`Reply::Contents`]
+    Read,
+}
+
+/// A line sent to the child process, like `{"id":1,"op":"list","path":"/srv"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Request {
+    pub id: u64,
+    pub op: Operation,
+    pub path: PathBuf,
+    /// Amount of bytes to [`Read`](Operation::Read) at most, like `"limit":4096`.
+    /// Left out to read the whole file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// The answer to a [`Request`], told apart by its fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Reply {
+    /// `{"id":1,"error":"No such file"}`
+    Error { error: String },
+    /// `{"id":1,"entries":["src","Cargo.toml"]}`
+    Entries { entries: Vec<String> },
+    /// `{"id":1,"is_dir":false,"len":42}`
+    Stat { is_dir: bool, len: u64 },
+    /// `{"id":1,"contents":"aGk="}`, the bytes of the file encoded as base64
+    Contents { contents: String },
+}
+
+/// A line received from the child process, the [`Reply`] to the request with the same `id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Response {
+    pub id: u64,
+    #[serde(flatten)]
+    pub reply: Reply,
+}
+
+/// A read only [`FileSystem`] served by a child process speaking JSON lines on stdin and stdout,
+/// like `ssh host ki-agent` or `docker exec -i container ki-agent`.
+///
+/// Each [`Request`] is answered by one [`Response`] line with the same `id`, in any order.
+/// Lines which are no valid response are ignored.
+///
+/// The [`FileSystem`] methods block until the response arrives or the
+/// [`timeout`](Self::timeout) passes. To load lazily without blocking,
+/// [`send`](Self::send) requests and [`poll`](Self::poll) for their replies, like on every tick.
+/// Replies to requests which timed out or were [`forget`](Self::forget)ten are dropped,
+/// of the others at most [`MAX_RECEIVED`] are kept.
+#[derive(Debug)]
+pub struct ProcessFs {
+    child: Child,
+    stdin: RefCell<ChildStdin>,
+    responses: Receiver<Response>,
+    /// Responses received but not asked for yet, by id so the oldest are dropped first
+    received: RefCell<BTreeMap<u64, Reply>>,
+    /// Requests whose replies are no longer waited for
+    abandoned: RefCell<HashSet<u64>>,
+    next_id: Cell<u64>,
+    timeout: Duration,
+}
+
+impl ProcessFs {
+    /// Spawn the command with piped stdin and stdout.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the command can not be spawned.
+    pub fn spawn(mut command: Command) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("Child process has no stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("Child process has no stdout"))?;
+
+        let (sender, responses) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Ok(response) = serde_json::from_str(&line) {
+                    if sender.send(response).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin: RefCell::new(stdin),
+            responses,
+            received: RefCell::new(BTreeMap::new()),
+            abandoned: RefCell::new(HashSet::new()),
+            next_id: Cell::new(1),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// How long to wait for a response. Defaults to [`DEFAULT_TIMEOUT`].
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send a request without waiting for its reply.
+    ///
+    /// Returns the id to [`poll`](Self::poll) for.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the child process does not take the request, like after it exited.
+    pub fn send(&self, op: Operation, path: &Path) -> io::Result<u64> {
+        self.send_request(op, path, None)
+    }
+
+    /// Send a request to read at most `limit` bytes of a file without waiting for its reply.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the child process does not take the request, see [`send`](Self::send).
+    pub fn send_read(&self, path: &Path, limit: u64) -> io::Result<u64> {
+        self.send_request(Operation::Read, path, Some(limit))
+    }
+
+    fn send_request(&self, op: Operation, path: &Path, limit: Option<u64>) -> io::Result<u64> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let request = Request {
+            id,
+            op,
+            path: path.to_path_buf(),
+            limit,
+        };
+        let mut line = serde_json::to_string(&request).map_err(io::Error::from)?;
+        line.push('\n');
+        let mut stdin = self.stdin.borrow_mut();
+        stdin.write_all(line.as_bytes())?;
+        stdin.flush()?;
+        Ok(id)
+    }
+
+    /// The reply to the request with the given id, when it arrived.
+    pub fn poll(&self, id: u64) -> Option<Reply> {
+        for response in self.responses.try_iter() {
+            self.keep(response);
+        }
+        self.received.borrow_mut().remove(&id)
+    }
+
+    /// Stop waiting for the reply to the request with the given id, it is dropped on arrival.
+    pub fn forget(&self, id: u64) {
+        if self.received.borrow_mut().remove(&id).is_none() {
+            self.abandoned.borrow_mut().insert(id);
+        }
+    }
+
+    /// Keep a response until it is asked for, unless its request was abandoned.
+    fn keep(&self, response: Response) {
+        if self.abandoned.borrow_mut().remove(&response.id) {
+            return;
+        }
+        let mut received = self.received.borrow_mut();
+        received.insert(response.id, response.reply);
+        while received.len() > MAX_RECEIVED {
+            received.pop_first();
+        }
+    }
+
+    /// Wait for the reply to the request with the given id.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the reply does not arrive within the [`timeout`](Self::timeout)
+    /// or the child process closed its stdout.
+    pub fn wait(&self, id: u64) -> io::Result<Reply> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(reply) = self.received.borrow_mut().remove(&id) {
+                return Ok(reply);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.responses.recv_timeout(remaining) {
+                Ok(response) if response.id == id => return Ok(response.reply),
+                Ok(response) => self.keep(response),
+                Err(RecvTimeoutError::Timeout) => {
+                    self.forget(id);
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("No response to request {id} within {:?}", self.timeout),
+                    ));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "Child process closed its output",
+                    ));
+                }
+            }
+        }
+    }
+
+    fn request(&self, op: Operation, path: &Path, limit: Option<u64>) -> io::Result<Reply> {
+        let id = self.send_request(op, path, limit)?;
+        match self.wait(id)? {
+            Reply::Error { error } => Err(io::Error::other(error)),
+            reply => Ok(reply),
+        }
+    }
+
+    fn read(&self, path: &Path, limit: Option<u64>) -> io::Result<Box<dyn Read>> {
+        match self.request(Operation::Read, path, limit)? {
+            Reply::Contents { contents } => {
+                let bytes = BASE64.decode(contents).map_err(|error| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid contents: {error}"),
+                    )
+                })?;
+                // Do not trust the child process to respect the limit
+                let limit = limit.unwrap_or(u64::MAX);
+                Ok(Box::new(Cursor::new(bytes).take(limit)))
+            }
+            reply => Err(unexpected(Operation::Read, &reply)),
+        }
+    }
+}
+
+impl Drop for ProcessFs {
+    fn drop(&mut self) {
+        _ = self.child.kill();
+        _ = self.child.wait();
+    }
+}
+
+fn unexpected(op: Operation, reply: &Reply) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Unexpected reply to {op:?}: {reply:?}"),
+    )
+}
+
+fn read_only(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::ReadOnlyFilesystem,
+        format!("{} is served read only by a child process", path.display()),
+    )
+}
+
+impl FileSystem for ProcessFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        match self.request(Operation::List, path, None)? {
+            Reply::Entries { entries } => Ok(entries.iter().map(|name| path.join(name)).collect()),
+            reply => Err(unexpected(Operation::List, &reply)),
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        match self.request(Operation::Stat, path, None)? {
+            Reply::Stat { is_dir, len } => Ok(Metadata {
+                is_dir,
+                len,
+                modified: None,
+            }),
+            reply => Err(unexpected(Operation::Stat, &reply)),
+        }
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        self.read(path, None)
+    }
+
+    fn open_limited(&self, path: &Path, limit: u64) -> io::Result<Box<dyn Read>> {
+        self.read(path, Some(limit))
+    }
+
+    fn rename(&self, from: &Path, _to: &Path) -> io::Result<()> {
+        Err(read_only(from))
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        Err(read_only(path))
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        Err(read_only(path))
+    }
+
+    fn write(&self, path: &Path, _contents: &[u8]) -> io::Result<()> {
+        Err(read_only(path))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Serves `/srv` with a directory `src` and a file `a.txt`, never answers for `/slow`.
+    const STUB: &str = r#"
+        while read -r line; do
+            id=$(printf '%s' "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+            case "$line" in
+                *slow*) ;;
+                *gone*) printf '{"id":%s,"error":"No such file"}\n' "$id" ;;
+                *'"op":"list"'*) printf '{"id":%s,"entries":["a.txt","src"]}\n' "$id" ;;
+                *'"op":"stat"'*src*) printf '{"id":%s,"is_dir":true,"len":0}\n' "$id" ;;
+                *'"op":"stat"'*) printf '{"id":%s,"is_dir":false,"len":5}\n' "$id" ;;
+                *'"op":"read"'*'"limit":2'*) printf '{"id":%s,"contents":"aGU="}\n' "$id" ;;
+                *'"op":"read"'*) printf '{"id":%s,"contents":"aGVsbG8="}\n' "$id" ;;
+            esac
+        done
+    "#;
+
+    fn stub() -> ProcessFs {
+        let mut command = Command::new("sh");
+        command.args(["-c", STUB]);
+        ProcessFs::spawn(command)
+            .unwrap()
+            .timeout(Duration::from_millis(500))
+    }
+
+    #[test]
+    fn serves_a_tree() {
+        let file_system = stub();
+        assert_eq!(
+            file_system.read_dir(Path::new("/srv")).unwrap(),
+            [PathBuf::from("/srv/a.txt"), PathBuf::from("/srv/src")]
+        );
+        assert!(file_system.is_dir(Path::new("/srv/src")));
+        assert_eq!(
+            file_system.metadata(Path::new("/srv/a.txt")).unwrap().len,
+            5
+        );
+
+        let mut contents = String::new();
+        file_system
+            .open(Path::new("/srv/a.txt"))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "hello");
+
+        let mut head = String::new();
+        file_system
+            .open_limited(Path::new("/srv/a.txt"), 2)
+            .unwrap()
+            .read_to_string(&mut head)
+            .unwrap();
+        assert_eq!(head, "he");
+
+        let error = file_system.metadata(Path::new("/srv/gone"));
+        assert_eq!(error.unwrap_err().to_string(), "No such file");
+        let write = file_system.write(Path::new("/srv/b.txt"), b"");
+        assert_eq!(write.unwrap_err().kind(), io::ErrorKind::ReadOnlyFilesystem);
+    }
+
+    #[test]
+    fn lazy_and_timeouts() {
+        let file_system = stub();
+        let slow = file_system
+            .send(Operation::List, Path::new("/slow"))
+            .unwrap();
+        let id = file_system
+            .send(Operation::List, Path::new("/srv"))
+            .unwrap();
+        let reply = file_system.wait(id).unwrap();
+        assert_eq!(
+            reply,
+            Reply::Entries {
+                entries: vec!["a.txt".to_string(), "src".to_string()]
+            }
+        );
+        assert_eq!(file_system.poll(slow), None);
+
+        let error = file_system.wait(slow).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn drops_abandoned_replies() {
+        let file_system = stub();
+        let forgotten = file_system
+            .send(Operation::List, Path::new("/srv"))
+            .unwrap();
+        file_system.forget(forgotten);
+        let id = file_system
+            .send(Operation::Stat, Path::new("/srv/a.txt"))
+            .unwrap();
+        file_system.wait(id).unwrap();
+        assert_eq!(file_system.poll(forgotten), None);
+        assert!(file_system.received.borrow().is_empty());
+        assert!(file_system.abandoned.borrow().is_empty());
+
+        for id in 0..=MAX_RECEIVED as u64 {
+            file_system.keep(Response {
+                id: 10_000 + id,
+                reply: Reply::Entries {
+                    entries: Vec::new(),
+                },
+            });
+        }
+        assert_eq!(file_system.received.borrow().len(), MAX_RECEIVED);
+        assert_eq!(file_system.poll(10_000), None);
+    }
+}
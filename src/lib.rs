@@ -1,3 +1,12 @@
+pub mod bookmarks_panel;
+pub mod breadcrumb;
+#[cfg(feature = "documents")]
+pub mod document;
 pub mod explorer;
 pub mod fs;
-mod tree;
+pub mod links;
+pub mod preview;
+pub mod status_line;
+#[cfg(test)]
+mod temp_dir;
+pub mod tree;
@@ -0,0 +1,568 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Read as _};
+use std::path::{Component, Path, PathBuf};
+
+use crate::explorer::state::ExplorerState;
+use crate::explorer::vault::is_note;
+use crate::fs::FileSystem;
+
+/// How a link was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    /// `[[Note]]`, `[[Note|alias]]` or `![[embedded.png]]`
+    Wiki,
+    /// `[text](path/to/note.md)`
+    Markdown,
+}
+
+/// A link from one note to another file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Link {
+    /// The note containing the link
+    pub source: PathBuf,
+    /// The target as written, without alias and heading
+    pub target: String,
+    pub kind: LinkKind,
+    /// Line of the link in the source, starting at 0
+    pub line: usize,
+    /// The linked file, `None` when the link is broken
+    pub resolved: Option<PathBuf>,
+}
+
+/// Forward and backward links between the Markdown notes below a root.
+///
+/// Build it from the [`Explorer::entries`](crate::explorer::Explorer::entries) and
+/// [`Explorer::fs`](crate::explorer::Explorer::fs) with [`build`](Self::build)
+/// and keep it up to date with [`update`](Self::update), [`reload`](Self::reload) and
+/// [`remove`](Self::remove) when files change. Only the changed note is parsed again,
+/// links are resolved again when files are added or removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkIndex {
+    root: PathBuf,
+    /// All known files, links can point to notes as well as attachments
+    files: BTreeSet<PathBuf>,
+    /// Known files by their lowercase file name
+    by_name: HashMap<String, BTreeSet<PathBuf>>,
+    outgoing: HashMap<PathBuf, Vec<Link>>,
+    /// Notes linking to a file
+    incoming: HashMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl LinkIndex {
+    /// Create an empty index for the notes below `root`.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Create an index of the given paths by reading all notes among them from the file system.
+    /// Notes which can not be read have no outgoing links.
+    #[must_use]
+    pub fn build<I>(fs: &dyn FileSystem, root: impl Into<PathBuf>, paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let mut index = Self::new(root);
+        let paths = paths
+            .into_iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .filter(|path| !fs.is_dir(path))
+            .collect::<Vec<_>>();
+        for path in &paths {
+            index.insert_file(path);
+        }
+        for path in paths.iter().filter(|path| is_note(path)) {
+            if let Ok(content) = read_note(fs, path) {
+                let links = parse_links(path, &content);
+                index.outgoing.insert(path.clone(), links);
+            }
+        }
+        index.resolve_all();
+        index
+    }
+
+    /// Add a file which is not a note, like an attachment, so links to it resolve.
+    pub fn add_file(&mut self, path: &Path) {
+        if self.insert_file(path) {
+            self.resolve_all();
+        }
+    }
+
+    /// Parse the links of the note at `path` with the given content again.
+    pub fn update(&mut self, path: &Path, content: &str) {
+        let added = self.insert_file(path);
+        let links = parse_links(path, content);
+        if added {
+            self.outgoing.insert(path.to_path_buf(), links);
+            self.resolve_all();
+            return;
+        }
+
+        self.unlink_incoming(path);
+        let links = links
+            .into_iter()
+            .map(|mut link| {
+                link.resolved = self.resolve(&link);
+                link
+            })
+            .collect::<Vec<_>>();
+        self.link_incoming(&links);
+        self.outgoing.insert(path.to_path_buf(), links);
+    }
+
+    /// Read the changed file at `path` from the file system and update the index.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the note can not be read.
+    pub fn reload(&mut self, fs: &dyn FileSystem, path: &Path) -> io::Result<()> {
+        if is_note(path) {
+            let content = read_note(fs, path)?;
+            self.update(path, &content);
+        } else {
+            self.add_file(path);
+        }
+        Ok(())
+    }
+
+    /// Forget a removed file. Links to it become broken.
+    pub fn remove(&mut self, path: &Path) {
+        if !self.files.remove(path) {
+            return;
+        }
+        if let Some(paths) = self.by_name.get_mut(&name_key(path)) {
+            paths.remove(path);
+        }
+        self.outgoing.remove(path);
+        self.resolve_all();
+    }
+
+    /// Links of the note at `path`.
+    #[must_use]
+    pub fn outgoing(&self, path: &Path) -> &[Link] {
+        self.outgoing.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Links of other notes pointing to `path`.
+    #[must_use]
+    pub fn backlinks(&self, path: &Path) -> Vec<&Link> {
+        let Some(sources) = self.incoming.get(path) else {
+            return Vec::new();
+        };
+        sources
+            .iter()
+            .flat_map(|source| self.outgoing(source))
+            .filter(|link| link.resolved.as_deref() == Some(path))
+            .collect()
+    }
+
+    /// Links of the node selected in the given [`ExplorerState`].
+    #[must_use]
+    pub fn selected_outgoing<Identifier>(&self, state: &ExplorerState<Identifier>) -> &[Link]
+    where
+        Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    {
+        state
+            .selected()
+            .last()
+            .map_or(&[], |identifier| self.outgoing(identifier.as_ref()))
+    }
+
+    /// Links pointing to the node selected in the given [`ExplorerState`].
+    #[must_use]
+    pub fn selected_backlinks<Identifier>(&self, state: &ExplorerState<Identifier>) -> Vec<&Link>
+    where
+        Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    {
+        state
+            .selected()
+            .last()
+            .map_or_else(Vec::new, |identifier| self.backlinks(identifier.as_ref()))
+    }
+
+    /// All links pointing to files which do not exist, sorted by source and line.
+    #[must_use]
+    pub fn broken_links(&self) -> Vec<&Link> {
+        let mut broken = self
+            .outgoing
+            .values()
+            .flatten()
+            .filter(|link| link.resolved.is_none())
+            .collect::<Vec<_>>();
+        broken.sort_by(|left, right| {
+            left.source
+                .cmp(&right.source)
+                .then(left.line.cmp(&right.line))
+        });
+        broken
+    }
+
+    /// Returns `true` when the file was not known before.
+    fn insert_file(&mut self, path: &Path) -> bool {
+        if !self.files.insert(path.to_path_buf()) {
+            return false;
+        }
+        self.by_name
+            .entry(name_key(path))
+            .or_default()
+            .insert(path.to_path_buf());
+        true
+    }
+
+    fn link_incoming(&mut self, links: &[Link]) {
+        for link in links {
+            if let Some(resolved) = &link.resolved {
+                self.incoming
+                    .entry(resolved.clone())
+                    .or_default()
+                    .insert(link.source.clone());
+            }
+        }
+    }
+
+    fn unlink_incoming(&mut self, source: &Path) {
+        for link in self.outgoing.get(source).into_iter().flatten() {
+            if let Some(sources) = link
+                .resolved
+                .as_ref()
+                .and_then(|resolved| self.incoming.get_mut(resolved))
+            {
+                sources.remove(source);
+            }
+        }
+    }
+
+    fn resolve_all(&mut self) {
+        let mut outgoing = core::mem::take(&mut self.outgoing);
+        self.incoming.clear();
+        for links in outgoing.values_mut() {
+            for link in links.iter_mut() {
+                link.resolved = self.resolve(link);
+            }
+            self.link_incoming(links);
+        }
+        self.outgoing = outgoing;
+    }
+
+    fn resolve(&self, link: &Link) -> Option<PathBuf> {
+        match link.kind {
+            LinkKind::Wiki => self.resolve_wiki(link),
+            LinkKind::Markdown => {
+                let target = percent_decode(&link.target);
+                let base = if target.starts_with('/') {
+                    self.root.as_path()
+                } else {
+                    link.source.parent().unwrap_or(&self.root)
+                };
+                let path = normalize(&base.join(target.trim_start_matches('/')));
+                self.files.contains(&path).then_some(path)
+            }
+        }
+    }
+
+    /// Resolve like Obsidian: by path relative to the root when the target contains a `/`,
+    /// otherwise by file name, preferring the folder of the source and then the shortest path.
+    fn resolve_wiki(&self, link: &Link) -> Option<PathBuf> {
+        let target = if Path::new(&link.target).extension().is_some() {
+            link.target.clone()
+        } else {
+            format!("{}.md", link.target)
+        };
+
+        if target.contains('/') {
+            let path = normalize(&self.root.join(&target));
+            return self.files.contains(&path).then_some(path);
+        }
+
+        let candidates = self.by_name.get(&target.to_lowercase())?;
+        let folder = link.source.parent();
+        candidates
+            .iter()
+            .find(|candidate| candidate.parent() == folder)
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .min_by_key(|candidate| candidate.components().count())
+            })
+            .cloned()
+    }
+}
+
+fn read_note(fs: &dyn FileSystem, path: &Path) -> io::Result<String> {
+    let mut content = String::new();
+    fs.open(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+fn name_key(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Resolve `.` and `..` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Decode `%20` and the like, leaving invalid sequences as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| core::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) if bytes[index] == b'%' => {
+                decoded.push(byte);
+                index += 3;
+            }
+            _ => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Whether the target of a Markdown link points outside of the vault or into the same note.
+fn is_external(target: &str) -> bool {
+    target.is_empty()
+        || target.starts_with('#')
+        || target.contains("://")
+        || target.starts_with("mailto:")
+}
+
+/// Find the wiki and Markdown links in the content of a note, skipping code.
+fn parse_links(source: &Path, content: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+    for (line_index, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let mut link = |target: &str, kind| {
+            links.push(Link {
+                source: source.to_path_buf(),
+                target: target.to_string(),
+                kind,
+                line: line_index,
+                resolved: None,
+            });
+        };
+
+        let mut rest = line;
+        while let Some(start) = rest.find(['`', '[']) {
+            let after = &rest[start..];
+            if let Some(code) = after.strip_prefix('`') {
+                rest = code.find('`').map_or("", |end| &code[end + 1..]);
+            } else if let Some(wiki) = after.strip_prefix("[[") {
+                // An unclosed `[[` is plain text, a link may still follow it
+                let Some(end) = wiki.find("]]").filter(|&end| !wiki[..end].contains("[[")) else {
+                    rest = wiki;
+                    continue;
+                };
+                let target = wiki[..end]
+                    .split(['|', '#'])
+                    .next()
+                    .unwrap_or_default()
+                    .trim();
+                if !target.is_empty() {
+                    link(target, LinkKind::Wiki);
+                }
+                rest = &wiki[end + 2..];
+            } else if let Some((target, remaining)) = markdown_target(after) {
+                if !is_external(target) {
+                    link(target, LinkKind::Markdown);
+                }
+                rest = remaining;
+            } else {
+                rest = &after[1..];
+            }
+        }
+    }
+    links
+}
+
+/// The target of a `[text](target "title")` link at the start of `text` and the text after it.
+fn markdown_target(text: &str) -> Option<(&str, &str)> {
+    let middle = text.find("](")?;
+    if text[1..middle].contains('[') {
+        return None;
+    }
+    let inner = &text[middle + 2..];
+    let end = inner.find(')')?;
+    let target = inner[..end].trim();
+    let target = target
+        .strip_prefix('<')
+        .and_then(|target| target.split_once('>'))
+        .map_or_else(
+            || target.split_whitespace().next().unwrap_or_default(),
+            |(target, _)| target,
+        );
+    let target = target.split('#').next().unwrap_or_default();
+    Some((target, &inner[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::memory::InMemoryFs;
+
+    fn targets(links: &[Link]) -> Vec<(&str, LinkKind, usize)> {
+        links
+            .iter()
+            .map(|link| (link.target.as_str(), link.kind, link.line))
+            .collect()
+    }
+
+    #[test]
+    fn parses_links() {
+        let content = "See [[Other|alias]] and [[Deep#Heading]], ![[image.png]]\n\
+            [docs](../docs/Guide%20One.md#intro) [web](https://example.com) [top](#top)\n\
+            `[[not a link]]`\n\
+            ```\n[[inside code]]\n```\n\
+            [text](<with space.md> \"title\")\n\
+            [[a [[b]] [[unclosed [c](c.md)";
+        let links = parse_links(Path::new("/vault/note.md"), content);
+        assert_eq!(
+            targets(&links),
+            [
+                ("Other", LinkKind::Wiki, 0),
+                ("Deep", LinkKind::Wiki, 0),
+                ("image.png", LinkKind::Wiki, 0),
+                ("../docs/Guide%20One.md", LinkKind::Markdown, 1),
+                ("with space.md", LinkKind::Markdown, 6),
+                ("b", LinkKind::Wiki, 7),
+                ("c.md", LinkKind::Markdown, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn backlinks_and_broken_links() {
+        let root = Path::new("/vault");
+        let mut index = LinkIndex::new(root);
+        index.add_file(&root.join("assets/image.png"));
+        index.update(&root.join("a.md"), "[[b]] [[missing]] ![[image.png]]");
+        index.update(&root.join("sub/b.md"), "[back](../a.md)");
+
+        assert_eq!(
+            index
+                .backlinks(&root.join("sub/b.md"))
+                .iter()
+                .map(|link| link.source.as_path())
+                .collect::<Vec<_>>(),
+            [root.join("a.md")]
+        );
+        assert_eq!(index.backlinks(&root.join("a.md")).len(), 1);
+        assert_eq!(index.backlinks(&root.join("assets/image.png")).len(), 1);
+        assert_eq!(
+            targets(
+                &index
+                    .broken_links()
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            ),
+            [("missing", LinkKind::Wiki, 0)]
+        );
+
+        // Creating the missing note resolves the link
+        index.update(&root.join("missing.md"), "");
+        assert!(index.broken_links().is_empty());
+        assert_eq!(index.backlinks(&root.join("missing.md")).len(), 1);
+
+        // Changing a note updates its links only
+        index.update(&root.join("a.md"), "nothing");
+        assert!(index.backlinks(&root.join("sub/b.md")).is_empty());
+        assert_eq!(index.backlinks(&root.join("a.md")).len(), 1);
+
+        // Removing a note breaks the links to it
+        index.remove(&root.join("a.md"));
+        assert_eq!(index.broken_links().len(), 1);
+        assert!(index.outgoing(&root.join("a.md")).is_empty());
+    }
+
+    #[test]
+    fn prefers_note_in_same_folder() {
+        let root = Path::new("/vault");
+        let mut index = LinkIndex::new(root);
+        index.update(&root.join("Topic.md"), "");
+        index.update(&root.join("deep/Topic.md"), "");
+        index.update(&root.join("deep/source.md"), "[[topic]]");
+        index.update(&root.join("other/source.md"), "[[Topic]] [[deep/Topic]]");
+
+        let resolved = |source: &str| {
+            index
+                .outgoing(&root.join(source))
+                .iter()
+                .map(|link| link.resolved.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            resolved("deep/source.md"),
+            [Some(root.join("deep/Topic.md"))]
+        );
+        assert_eq!(
+            resolved("other/source.md"),
+            [
+                Some(root.join("Topic.md")),
+                Some(root.join("deep/Topic.md"))
+            ]
+        );
+    }
+
+    #[test]
+    fn build_from_file_system() {
+        let root = Path::new("/vault");
+        let file_system = InMemoryFs::new()
+            .with_file("/vault/a.md", "[[b]] ![[image.png]]")
+            .with_file("/vault/sub/b.md", "[back](../a.md)")
+            .with_file("/vault/image.png", "");
+        let paths = [
+            root.join("a.md"),
+            root.join("sub"),
+            root.join("sub/b.md"),
+            root.join("image.png"),
+        ];
+        let mut index = LinkIndex::build(&file_system, root, &paths);
+        assert!(index.broken_links().is_empty());
+        assert_eq!(index.backlinks(&root.join("a.md")).len(), 1);
+        assert_eq!(index.backlinks(&root.join("image.png")).len(), 1);
+        assert!(index.outgoing(&root.join("sub")).is_empty());
+
+        file_system
+            .write(&root.join("sub/b.md"), b"[[missing]]")
+            .unwrap();
+        index.reload(&file_system, &root.join("sub/b.md")).unwrap();
+        assert!(index.backlinks(&root.join("a.md")).is_empty());
+        assert_eq!(index.broken_links().len(), 1);
+        assert!(index.reload(&file_system, &root.join("gone.md")).is_err());
+    }
+}
@@ -0,0 +1,815 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use std::collections::HashMap;
+use std::io::{self, Read as _};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, StatefulWidget, Widget as _};
+use unicode_width::UnicodeWidthChar as _;
+
+use crate::explorer::state::ExplorerState;
+use crate::fs::{FileSystem, StdFs};
+use crate::preview::highlight::{HighlightTheme, Highlighter, LineState, Token};
+
+pub mod highlight;
+
+/// Files bigger than this are only previewed partially.
+pub const DEFAULT_MAX_SIZE: u64 = 256 * 1024;
+
+/// Amount of previews kept in the cache of a [`PreviewState`].
+const CACHE_CAPACITY: usize = 64;
+
+/// Columns a tab is expanded to.
+const TAB_WIDTH: usize = 4;
+
+/// An entry of a previewed directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// What is shown for a previewed path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewContent {
+    /// Lines of a text file. `truncated` when the file is bigger than the size cap.
+    Text { lines: Vec<String>, truncated: bool },
+    /// A file which is not valid UTF-8 or contains NUL bytes.
+    Binary { size: u64 },
+    /// Listing of a directory, directories first.
+    Directory {
+        entries: Vec<DirectoryEntry>,
+        directories: usize,
+        files: usize,
+    },
+    /// The path could not be read.
+    Error(String),
+}
+
+impl PreviewContent {
+    /// Read the preview of the given path, reading at most `max_size` bytes of a file.
+    #[must_use]
+    pub fn read(path: &Path, max_size: u64) -> Self {
+        Self::read_from(&StdFs, path, max_size)
+    }
+
+    /// Read the preview of the given path from the given [`FileSystem`],
+    /// see [`read`](Self::read).
+    #[must_use]
+    pub fn read_from(fs: &dyn FileSystem, path: &Path, max_size: u64) -> Self {
+        let result = if fs.is_dir(path) {
+            read_directory(fs, path)
+        } else {
+            read_file(fs, path, max_size)
+        };
+        result.unwrap_or_else(|error| Self::Error(error.to_string()))
+    }
+
+    /// Amount of rows needed to show the content, wrapped at the given width when `Some`.
+    fn rows(&self, wrap_width: Option<usize>) -> Vec<Row<'_>> {
+        match self {
+            Self::Text { lines, truncated } => {
+                let mut rows = Vec::new();
+                for (index, line) in lines.iter().enumerate() {
+                    let chunks =
+                        wrap_width.map_or_else(|| vec![line.as_str()], |width| wrap(line, width));
+                    let mut start = 0;
+                    for text in chunks {
+                        rows.push(Row::Line { index, start, text });
+                        start += text.len();
+                    }
+                }
+                if *truncated {
+                    rows.push(Row::Note("\u{2026} truncated".to_string()));
+                }
+                rows
+            }
+            Self::Binary { size } => vec![Row::Note(format!("Binary file, {size} bytes"))],
+            Self::Directory {
+                entries,
+                directories,
+                files,
+            } => {
+                let summary = format!("{directories} directories, {files} files");
+                core::iter::once(Row::Note(summary))
+                    .chain(entries.iter().map(Row::Entry))
+                    .collect()
+            }
+            Self::Error(message) => vec![Row::Note(message.clone())],
+        }
+    }
+}
+
+/// A single rendered row of a preview.
+enum Row<'content> {
+    /// Part of the line at `index` starting at byte `start`, wrapped lines have multiple rows
+    Line {
+        index: usize,
+        start: usize,
+        text: &'content str,
+    },
+    Entry(&'content DirectoryEntry),
+    Note(String),
+}
+
+fn read_file(fs: &dyn FileSystem, path: &Path, max_size: u64) -> io::Result<PreviewContent> {
+    let size = fs.metadata(path)?.len;
+    let mut bytes = Vec::new();
+    fs.open_limited(path, max_size)?.read_to_end(&mut bytes)?;
+
+    let text = match core::str::from_utf8(&bytes) {
+        Ok(text) => text,
+        // The size cap might cut a multibyte char in half
+        Err(error) if error.error_len().is_none() => {
+            core::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return Ok(PreviewContent::Binary { size }),
+    };
+    if text.contains('\0') {
+        return Ok(PreviewContent::Binary { size });
+    }
+
+    Ok(PreviewContent::Text {
+        lines: text
+            .lines()
+            .map(|line| line.replace('\t', &" ".repeat(TAB_WIDTH)))
+            .collect(),
+        truncated: size > max_size,
+    })
+}
+
+fn read_directory(fs: &dyn FileSystem, path: &Path) -> io::Result<PreviewContent> {
+    let mut entries = fs
+        .read_dir(path)?
+        .into_iter()
+        .map(|entry| DirectoryEntry {
+            name: entry
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            is_dir: fs.is_dir(&entry),
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|left, right| {
+        right
+            .is_dir
+            .cmp(&left.is_dir)
+            .then_with(|| left.name.cmp(&right.name))
+    });
+
+    let directories = entries.iter().filter(|entry| entry.is_dir).count();
+    Ok(PreviewContent::Directory {
+        files: entries.len() - directories,
+        directories,
+        entries,
+    })
+}
+
+/// Split the line into chunks of at most `width` columns.
+fn wrap(line: &str, width: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut columns = 0;
+    for (index, char) in line.char_indices() {
+        let char_width = char.width().unwrap_or(0);
+        if columns + char_width > width && index > start {
+            chunks.push(&line[start..index]);
+            start = index;
+            columns = 0;
+        }
+        columns += char_width;
+    }
+    chunks.push(&line[start..]);
+    chunks
+}
+
+#[derive(Debug, Clone)]
+struct CachedPreview {
+    modified: Option<SystemTime>,
+    content: Rc<PreviewContent>,
+    last_used: u64,
+}
+
+/// Keeps track of the previewed path, its scroll position and the previews read so far.
+#[derive(Debug, Clone)]
+pub struct PreviewState {
+    /// The currently previewed path
+    pub path: Option<PathBuf>,
+    /// The first row shown
+    pub offset: usize,
+    /// Files bigger than this are only previewed partially
+    pub max_size: u64,
+
+    pub last_area: Rect,
+    /// Amount of rows of the content on last render
+    pub last_rows: usize,
+
+    content: Option<Rc<PreviewContent>>,
+    /// Where previewed paths are read from, the local disk by default
+    fs: Rc<dyn FileSystem>,
+    highlighter: Option<Rc<dyn Highlighter>>,
+    /// Tokens of the first lines of the content and the state at the end of each line.
+    /// Only grows as far as lines were visible.
+    highlighted: Vec<(Vec<Token>, LineState)>,
+    /// Previews keyed by path, only valid as long as the modification time matches.
+    /// Paths without modification time are not cached
+    cache: HashMap<PathBuf, CachedPreview>,
+    loads: u64,
+}
+
+impl Default for PreviewState {
+    fn default() -> Self {
+        Self {
+            path: None,
+            offset: 0,
+            max_size: DEFAULT_MAX_SIZE,
+            last_area: Rect::default(),
+            last_rows: 0,
+            content: None,
+            fs: Rc::new(StdFs),
+            highlighter: None,
+            highlighted: Vec::new(),
+            cache: HashMap::new(),
+            loads: 0,
+        }
+    }
+}
+
+impl PreviewState {
+    /// Highlight the lines of text files with the given [`Highlighter`].
+    #[must_use]
+    pub fn highlighter(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.highlighter = Some(Rc::new(highlighter));
+        self.highlighted.clear();
+        self
+    }
+
+    /// Read previewed paths from the given [`FileSystem`], like the one of the
+    /// [`Explorer`](crate::explorer::Explorer) with `explorer.fs.clone()`.
+    #[must_use]
+    pub fn file_system(mut self, fs: impl FileSystem + 'static) -> Self {
+        self.fs = Rc::new(fs);
+        self.cache.clear();
+        self
+    }
+
+    /// The content of the currently previewed path.
+    #[must_use]
+    pub fn content(&self) -> Option<&PreviewContent> {
+        self.content.as_deref()
+    }
+
+    /// Preview the node selected in the given [`ExplorerState`].
+    ///
+    /// Returns `true` when the previewed path changed.
+    pub fn show_selected<Identifier>(&mut self, state: &ExplorerState<Identifier>) -> bool
+    where
+        Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    {
+        match state.selected().last() {
+            Some(identifier) => self.show(identifier.as_ref()),
+            None => self.clear(),
+        }
+    }
+
+    /// Preview the given path. Previews of unchanged paths are taken from the cache.
+    /// When the [`FileSystem`] keeps no modification times, the path is read on every call
+    /// as its changes can not be told.
+    ///
+    /// Returns `true` when the previewed path changed.
+    pub fn show(&mut self, path: &Path) -> bool {
+        let changed = self.path.as_deref() != Some(path);
+        if changed {
+            self.offset = 0;
+        }
+
+        let modified = self
+            .fs
+            .metadata(path)
+            .ok()
+            .and_then(|metadata| metadata.modified);
+        self.loads += 1;
+        let content = match self.cache.get_mut(path) {
+            Some(cached) if modified.is_some() && cached.modified == modified => {
+                cached.last_used = self.loads;
+                Rc::clone(&cached.content)
+            }
+            _ => {
+                let content = Rc::new(PreviewContent::read_from(&*self.fs, path, self.max_size));
+                if modified.is_some() {
+                    self.insert_cached(path, modified, Rc::clone(&content));
+                }
+                content
+            }
+        };
+
+        // Content read again unchanged keeps its highlighting
+        let same_content = self.content.as_ref().is_some_and(|current| {
+            Rc::ptr_eq(current, &content) || current.as_ref() == content.as_ref()
+        });
+        if changed || !same_content {
+            self.highlighted.clear();
+        }
+        self.path = Some(path.to_path_buf());
+        self.content = Some(content);
+        changed
+    }
+
+    /// Stop previewing.
+    ///
+    /// Returns `true` when something was previewed before.
+    pub fn clear(&mut self) -> bool {
+        self.offset = 0;
+        self.content = None;
+        self.highlighted.clear();
+        self.path.take().is_some()
+    }
+
+    /// Forget all cached previews.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Forget the cached preview of the path, like when it changed.
+    /// The current preview is read again on the next [`show`](Self::show).
+    pub fn invalidate(&mut self, path: &Path) {
+        self.cache.remove(path);
+    }
+
+    /// Highlight the lines of the content up to the line at `last` which were not highlighted yet.
+    fn highlight_until(&mut self, last: usize) {
+        let (Some(highlighter), Some(path), Some(content)) =
+            (&self.highlighter, &self.path, &self.content)
+        else {
+            return;
+        };
+        let PreviewContent::Text { lines, .. } = content.as_ref() else {
+            return;
+        };
+
+        let mut state = self
+            .highlighted
+            .last()
+            .map_or_else(LineState::default, |(_, state)| *state);
+        for line in lines.iter().take(last + 1).skip(self.highlighted.len()) {
+            let (tokens, next) = highlighter.highlight_line(path, line, state);
+            self.highlighted.push((tokens, next));
+            state = next;
+        }
+    }
+
+    fn insert_cached(
+        &mut self,
+        path: &Path,
+        modified: Option<SystemTime>,
+        content: Rc<PreviewContent>,
+    ) {
+        if self.cache.len() >= CACHE_CAPACITY {
+            let least_recently_used = self
+                .cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(path, _)| path.clone());
+            if let Some(path) = least_recently_used {
+                self.cache.remove(&path);
+            }
+        }
+        self.cache.insert(
+            path.to_path_buf(),
+            CachedPreview {
+                modified,
+                content,
+                last_used: self.loads,
+            },
+        );
+    }
+
+    /// Scroll the specified amount of rows up
+    ///
+    /// Returns `true` when the scroll position changed.
+    pub const fn scroll_up(&mut self, rows: usize) -> bool {
+        let before = self.offset;
+        self.offset = self.offset.saturating_sub(rows);
+        before != self.offset
+    }
+
+    /// Scroll the specified amount of rows down
+    ///
+    /// Returns `true` when the scroll position changed.
+    /// Returns `false` when the last row is already visible.
+    pub fn scroll_down(&mut self, rows: usize) -> bool {
+        let before = self.offset;
+        let max_offset = self
+            .last_rows
+            .saturating_sub(self.last_area.height as usize);
+        self.offset = self.offset.saturating_add(rows).min(max_offset);
+        before != self.offset
+    }
+}
+
+/// Shows the content of the path previewed by a [`PreviewState`] next to the tree.
+///
+/// Text files are shown with line numbers, directories as a listing with counts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    block: Option<Block<'static>>,
+    /// Style used as a base style for the widget
+    style: Style,
+    line_number_style: Style,
+    directory_style: Style,
+    /// Style of notes like the directory summary or errors
+    note_style: Style,
+    /// Styles of the tokens found by the [`Highlighter`] of the [`PreviewState`]
+    theme: HighlightTheme,
+    wrap: bool,
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        Self {
+            block: None,
+            style: Style::new(),
+            line_number_style: Style::new().add_modifier(Modifier::DIM),
+            directory_style: Style::new().add_modifier(Modifier::BOLD),
+            note_style: Style::new().add_modifier(Modifier::ITALIC),
+            theme: HighlightTheme::default(),
+            wrap: true,
+        }
+    }
+}
+
+impl Preview {
+    #[must_use]
+    pub fn block(mut self, block: Block<'static>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    #[must_use]
+    pub const fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn line_number_style(mut self, style: Style) -> Self {
+        self.line_number_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn directory_style(mut self, style: Style) -> Self {
+        self.directory_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn note_style(mut self, style: Style) -> Self {
+        self.note_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn theme(mut self, theme: HighlightTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Wrap lines longer than the area instead of cutting them off. Defaults to `true`.
+    #[must_use]
+    pub const fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+}
+
+impl StatefulWidget for Preview {
+    type State = PreviewState;
+
+    #[expect(clippy::cast_possible_truncation)]
+    fn render(self, full_area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        buf.set_style(full_area, self.style);
+
+        let area = self.block.map_or(full_area, |block| {
+            let inner_area = block.inner(full_area);
+            block.render(full_area, buf);
+            inner_area
+        });
+
+        state.last_area = area;
+        state.last_rows = 0;
+        if area.width < 1 || area.height < 1 {
+            return;
+        }
+        let Some(content) = state.content.clone() else {
+            return;
+        };
+
+        let gutter_width = match content.as_ref() {
+            PreviewContent::Text { lines, .. } => lines.len().to_string().len() + 1,
+            _ => 0,
+        };
+        let text_width = (area.width as usize).saturating_sub(gutter_width);
+        let rows = content.rows(self.wrap.then_some(text_width.max(1)));
+
+        state.last_rows = rows.len();
+        state.offset = state
+            .offset
+            .min(rows.len().saturating_sub(area.height as usize));
+
+        let visible = rows.iter().skip(state.offset).take(area.height as usize);
+        if let Some(last) = visible.rev().find_map(|row| match row {
+            Row::Line { index, .. } => Some(*index),
+            _ => None,
+        }) {
+            state.highlight_until(last);
+        }
+
+        for (y, row) in (area.top()..area.bottom()).zip(rows.iter().skip(state.offset)) {
+            let width = area.width as usize;
+            match row {
+                Row::Line { index, start, text } => {
+                    if *start == 0 {
+                        let number = format!("{:>width$} ", index + 1, width = gutter_width - 1);
+                        buf.set_stringn(area.x, y, number, width, self.line_number_style);
+                    }
+                    let x = area.x + gutter_width.min(width) as u16;
+                    let tokens = state
+                        .highlighted
+                        .get(*index)
+                        .map_or(&[][..], |(tokens, _)| tokens.as_slice());
+                    let line = styled_chunk(text, *start, tokens, &self.theme);
+                    buf.set_line(x, y, &line, text_width as u16);
+                }
+                Row::Entry(entry) => {
+                    if entry.is_dir {
+                        let name = format!("{}/", entry.name);
+                        buf.set_stringn(area.x, y, name, width, self.directory_style);
+                    } else {
+                        buf.set_stringn(area.x, y, &entry.name, width, Style::new());
+                    }
+                }
+                Row::Note(note) => {
+                    buf.set_stringn(area.x, y, note, width, self.note_style);
+                }
+            }
+        }
+    }
+}
+
+/// Style the part of a line starting at byte `start` with the tokens of the line.
+fn styled_chunk<'text>(
+    text: &'text str,
+    start: usize,
+    tokens: &[Token],
+    theme: &HighlightTheme,
+) -> Line<'text> {
+    // Positions relative to the chunk. Ranges of a highlighter might end within a char
+    let boundary = |index: usize| {
+        let mut index = index.saturating_sub(start).min(text.len());
+        while !text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for token in tokens {
+        let token_start = boundary(token.range.start).max(position);
+        let token_end = boundary(token.range.end);
+        if token_start >= token_end {
+            continue;
+        }
+        if token_start > position {
+            spans.push(Span::raw(&text[position..token_start]));
+        }
+        spans.push(Span::styled(
+            &text[token_start..token_end],
+            theme.style(token.kind),
+        ));
+        position = token_end;
+    }
+    if position < text.len() {
+        spans.push(Span::raw(&text[position..]));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::fs::memory::InMemoryFs;
+    use crate::temp_dir::TempDir;
+
+    #[track_caller]
+    fn render(preview: Preview, width: u16, height: u16, state: &mut PreviewState) -> Buffer {
+        let area = Rect::new(0, 0, width, height);
+        let mut buffer = Buffer::empty(area);
+        preview.render(area, &mut buffer, state);
+        buffer
+    }
+
+    #[test]
+    fn text_with_line_numbers_and_wrapping() {
+        let file = Path::new("/project/main.rs");
+        let file_system =
+            InMemoryFs::new().with_file(file, "fn main() {\n    println!(\"hello\");\n}\n");
+
+        let mut state = PreviewState::default().file_system(file_system);
+        assert!(state.show(file));
+        let preview = Preview::default()
+            .line_number_style(Style::new())
+            .note_style(Style::new());
+        let buffer = render(preview.clone(), 14, 5, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "1 fn main() { ",
+            "2     println!",
+            "  (\"hello\");  ",
+            "3 }           ",
+            "              ",
+        ]);
+        assert_eq!(buffer, expected);
+
+        assert!(!state.scroll_down(10));
+        _ = render(preview, 14, 3, &mut state);
+        assert!(state.scroll_down(10));
+        assert_eq!(state.offset, 1);
+    }
+
+    #[test]
+    fn highlights_visible_lines_only() {
+        let file = Path::new("/project/lib.rs");
+        let file_system =
+            InMemoryFs::new().with_file(file, "fn a() {}\n/* one\ntwo */\nfn b() {}\n");
+
+        let mut state = PreviewState::default()
+            .file_system(file_system)
+            .highlighter(highlight::BasicHighlighter);
+        state.show(file);
+        let theme = HighlightTheme::default();
+        let preview = Preview::default().theme(theme);
+        let buffer = render(preview.clone(), 12, 2, &mut state);
+        assert_eq!(state.highlighted.len(), 2);
+        assert_eq!(buffer[(2, 0)].fg, theme.keyword.fg.unwrap());
+        assert_eq!(buffer[(2, 1)].fg, theme.comment.fg.unwrap());
+
+        state.scroll_down(2);
+        let buffer = render(preview, 12, 2, &mut state);
+        assert_eq!(state.highlighted.len(), 4);
+        assert_eq!(buffer[(2, 0)].fg, theme.comment.fg.unwrap());
+        assert_eq!(buffer[(2, 1)].fg, theme.keyword.fg.unwrap());
+    }
+
+    #[test]
+    fn binary_and_truncated() {
+        let binary = Path::new("/project/image.png");
+        let large = Path::new("/project/large.txt");
+        let file_system = InMemoryFs::new()
+            .with_file(binary, [0x89, b'P', b'N', b'G', 0, 1, 2])
+            .with_file(large, "line\n".repeat(10));
+
+        assert_eq!(
+            PreviewContent::read_from(&file_system, binary, DEFAULT_MAX_SIZE),
+            PreviewContent::Binary { size: 7 }
+        );
+        assert_eq!(
+            PreviewContent::read_from(&file_system, large, 12),
+            PreviewContent::Text {
+                lines: vec!["line".to_string(), "line".to_string(), "li".to_string()],
+                truncated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn directory_listing() {
+        let file_system = InMemoryFs::new()
+            .with_dir("/project/src")
+            .with_file("/project/Cargo.toml", "")
+            .with_file("/project/README.md", "");
+
+        let mut state = PreviewState::default().file_system(file_system);
+        state.show(Path::new("/project"));
+        let preview = Preview::default()
+            .directory_style(Style::new())
+            .note_style(Style::new());
+        let buffer = render(preview, 22, 4, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "1 directories, 2 files",
+            "src/                  ",
+            "Cargo.toml            ",
+            "README.md             ",
+        ]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_modification() {
+        let dir = TempDir::new("preview-cache");
+        let file = dir.join("notes.md");
+        fs::write(&file, "first").unwrap();
+
+        let mut state = PreviewState::default();
+        state.show(&file);
+        let first = Rc::clone(state.content.as_ref().unwrap());
+        assert!(!state.show(&file));
+        assert!(Rc::ptr_eq(&first, state.content.as_ref().unwrap()));
+
+        fs::write(&file, "second").unwrap();
+        fs::File::options()
+            .append(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(SystemTime::now() + core::time::Duration::from_secs(10))
+            .unwrap();
+        state.show(&file);
+        assert_eq!(
+            state.content(),
+            Some(&PreviewContent::Text {
+                lines: vec!["second".to_string()],
+                truncated: false,
+            })
+        );
+    }
+
+    #[test]
+    fn reads_from_file_system() {
+        let file_system = InMemoryFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_dir("/project/docs");
+        let mut state = PreviewState::default().file_system(file_system);
+
+        state.show(Path::new("/project/src/main.rs"));
+        assert_eq!(
+            state.content(),
+            Some(&PreviewContent::Text {
+                lines: vec!["fn main() {}".to_string()],
+                truncated: false,
+            })
+        );
+        state.show(Path::new("/project"));
+        let Some(PreviewContent::Directory {
+            directories, files, ..
+        }) = state.content()
+        else {
+            panic!("Expected a directory listing");
+        };
+        assert_eq!((*directories, *files), (2, 0));
+    }
+
+    #[test]
+    fn reads_again_without_modification_time() {
+        let file = Path::new("/project/notes.md");
+        let file_system = Rc::new(InMemoryFs::new().with_file(file, "first"));
+        let mut state = PreviewState::default().file_system(Rc::clone(&file_system));
+        state.show(file);
+        assert!(state.cache.is_empty());
+
+        // Same length, so only reading again tells the change
+        file_system.write(file, b"later").unwrap();
+        state.show(file);
+        assert_eq!(
+            state.content(),
+            Some(&PreviewContent::Text {
+                lines: vec!["later".to_string()],
+                truncated: false,
+            })
+        );
+
+        file_system.write(file, b"second").unwrap();
+        state.show(file);
+        assert_eq!(
+            state.content(),
+            Some(&PreviewContent::Text {
+                lines: vec!["second".to_string()],
+                truncated: false,
+            })
+        );
+    }
+
+    #[test]
+    fn tokens_within_chars() {
+        let theme = HighlightTheme::default();
+        let token = |range| Token {
+            range,
+            kind: highlight::TokenKind::Keyword,
+        };
+        // `é` takes the bytes 1..3, `ü` 5..7 of the line
+        let line = styled_chunk("aé bü", 0, &[token(0..2), token(4..6)], &theme);
+        assert_eq!(line.to_string(), "aé bü");
+        assert_eq!(line.spans[0].content, "a");
+        // A wrapped chunk starting at byte 3
+        let line = styled_chunk(" bü", 3, &[token(2..6)], &theme);
+        assert_eq!(line.to_string(), " bü");
+    }
+}
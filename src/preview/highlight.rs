@@ -0,0 +1,556 @@
+use core::fmt::Debug;
+use core::ops::Range;
+use std::path::Path;
+
+use ratatui::style::{Color, Modifier, Style};
+
+/// Kind of a highlighted part of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Keyword,
+    Type,
+    String,
+    Number,
+    Comment,
+    /// Literals like `true`, `false` or `null`
+    Literal,
+    /// Keys of TOML and JSON
+    Key,
+    /// Markdown headings and TOML tables
+    Heading,
+    Emphasis,
+    /// Markdown code spans and code blocks
+    Code,
+    Link,
+}
+
+/// A highlighted part of a line, `range` being byte offsets into the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+/// What is still open at the end of a line, like a block comment.
+///
+/// The value is up to the [`Highlighter`], the default means nothing is open.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineState(pub u32);
+
+/// Splits the lines of a previewed file into [`Token`]s.
+///
+/// Lines are highlighted one after another, each starting with the [`LineState`] the line
+/// before ended with. This way only the lines up to the visible ones need to be highlighted.
+pub trait Highlighter: Debug {
+    /// Highlight a line of the file at `path`, starting in the given state.
+    ///
+    /// Returns the tokens of the line and the state at its end.
+    fn highlight_line(&self, path: &Path, line: &str, state: LineState) -> (Vec<Token>, LineState);
+}
+
+/// Maps the [`TokenKind`]s to [`Style`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HighlightTheme {
+    pub keyword: Style,
+    pub type_: Style,
+    pub string: Style,
+    pub number: Style,
+    pub comment: Style,
+    pub literal: Style,
+    pub key: Style,
+    pub heading: Style,
+    pub emphasis: Style,
+    pub code: Style,
+    pub link: Style,
+}
+
+impl Default for HighlightTheme {
+    fn default() -> Self {
+        Self {
+            keyword: Style::new().fg(Color::Magenta),
+            type_: Style::new().fg(Color::Yellow),
+            string: Style::new().fg(Color::Green),
+            number: Style::new().fg(Color::Cyan),
+            comment: Style::new().fg(Color::DarkGray),
+            literal: Style::new().fg(Color::Cyan),
+            key: Style::new().fg(Color::Blue),
+            heading: Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            emphasis: Style::new().add_modifier(Modifier::ITALIC),
+            code: Style::new().fg(Color::Green),
+            link: Style::new()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::UNDERLINED),
+        }
+    }
+}
+
+impl HighlightTheme {
+    #[must_use]
+    pub const fn style(&self, kind: TokenKind) -> Style {
+        match kind {
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Type => self.type_,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.comment,
+            TokenKind::Literal => self.literal,
+            TokenKind::Key => self.key,
+            TokenKind::Heading => self.heading,
+            TokenKind::Emphasis => self.emphasis,
+            TokenKind::Code => self.code,
+            TokenKind::Link => self.link,
+        }
+    }
+}
+
+/// Languages known to the [`BasicHighlighter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Rust,
+    Markdown,
+    Toml,
+    Json,
+}
+
+impl Language {
+    /// Guess the language from the extension of the path.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "md" | "markdown" => Some(Self::Markdown),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Lightweight highlighter for keywords, strings, comments and the like.
+///
+/// Knows [`Language`]s by the extension of the file, other files are not highlighted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BasicHighlighter;
+
+const NORMAL: LineState = LineState(0);
+const BLOCK_COMMENT: LineState = LineState(1);
+const MULTILINE_STRING: LineState = LineState(2);
+const CODE_BLOCK: LineState = LineState(3);
+
+impl Highlighter for BasicHighlighter {
+    fn highlight_line(&self, path: &Path, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+        match Language::from_path(path) {
+            Some(Language::Rust) => highlight_code(line, state, &RUST),
+            Some(Language::Toml) => highlight_code(line, state, &TOML),
+            Some(Language::Json) => highlight_code(line, state, &JSON),
+            Some(Language::Markdown) => highlight_markdown(line, state),
+            None => (Vec::new(), NORMAL),
+        }
+    }
+}
+
+/// What a code-like language looks like to [`highlight_code`].
+#[expect(clippy::struct_excessive_bools)]
+struct Syntax {
+    line_comment: Option<&'static str>,
+    block_comment: Option<(&'static str, &'static str)>,
+    multiline_string: Option<&'static str>,
+    single_quote_strings: bool,
+    keywords: &'static [&'static str],
+    literals: &'static [&'static str],
+    /// Capitalized identifiers are types
+    types: bool,
+    /// Strings and identifiers followed by `=` or `:` are keys
+    keys: bool,
+    /// Lines starting with `[` are headings (TOML tables)
+    table_headings: bool,
+}
+
+const RUST: Syntax = Syntax {
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    multiline_string: None,
+    single_quote_strings: false,
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    literals: &["true", "false"],
+    types: true,
+    keys: false,
+    table_headings: false,
+};
+
+const TOML: Syntax = Syntax {
+    line_comment: Some("#"),
+    block_comment: None,
+    multiline_string: Some("\"\"\""),
+    single_quote_strings: true,
+    keywords: &[],
+    literals: &["true", "false"],
+    types: false,
+    keys: true,
+    table_headings: true,
+};
+
+const JSON: Syntax = Syntax {
+    line_comment: None,
+    block_comment: None,
+    multiline_string: None,
+    single_quote_strings: false,
+    keywords: &[],
+    literals: &["true", "false", "null"],
+    types: false,
+    keys: true,
+    table_headings: false,
+};
+
+const fn token(range: Range<usize>, kind: TokenKind) -> Token {
+    Token { range, kind }
+}
+
+/// Whether the next non whitespace char after `index` makes the part before a key.
+fn is_key(line: &str, index: usize) -> bool {
+    matches!(line[index..].trim_start().chars().next(), Some('=' | ':'))
+}
+
+/// End of a string starting with `quote` at `start`, honoring backslash escapes.
+/// `None` when the string is not closed on this line.
+fn string_end(line: &str, start: usize, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (index, char) in line[start + quote.len_utf8()..].char_indices() {
+        match char {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if char == quote => return Some(start + quote.len_utf8() + index + char.len_utf8()),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[expect(clippy::too_many_lines)]
+fn highlight_code(line: &str, state: LineState, syntax: &Syntax) -> (Vec<Token>, LineState) {
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    // Continue what the previous line left open
+    match state {
+        BLOCK_COMMENT => {
+            let end_marker = syntax.block_comment.map_or("*/", |(_, end)| end);
+            let Some(end) = line.find(end_marker) else {
+                tokens.push(token(0..line.len(), TokenKind::Comment));
+                return (tokens, BLOCK_COMMENT);
+            };
+            index = end + end_marker.len();
+            tokens.push(token(0..index, TokenKind::Comment));
+        }
+        MULTILINE_STRING => {
+            let marker = syntax.multiline_string.unwrap_or("\"\"\"");
+            let Some(end) = line.find(marker) else {
+                tokens.push(token(0..line.len(), TokenKind::String));
+                return (tokens, MULTILINE_STRING);
+            };
+            index = end + marker.len();
+            tokens.push(token(0..index, TokenKind::String));
+        }
+        _ => {}
+    }
+
+    if syntax.table_headings && index == 0 && line.trim_start().starts_with('[') {
+        let end = line.find(']').map_or(line.len(), |end| end + 1);
+        tokens.push(token(0..end, TokenKind::Heading));
+        index = end;
+    }
+
+    while index < line.len() {
+        let rest = &line[index..];
+
+        if let Some(marker) = syntax.line_comment {
+            if rest.starts_with(marker) {
+                tokens.push(token(index..line.len(), TokenKind::Comment));
+                break;
+            }
+        }
+
+        if let Some((start_marker, end_marker)) = syntax.block_comment {
+            if rest.starts_with(start_marker) {
+                let body = index + start_marker.len();
+                let Some(end) = line[body..].find(end_marker) else {
+                    tokens.push(token(index..line.len(), TokenKind::Comment));
+                    return (tokens, BLOCK_COMMENT);
+                };
+                let end = body + end + end_marker.len();
+                tokens.push(token(index..end, TokenKind::Comment));
+                index = end;
+                continue;
+            }
+        }
+
+        if let Some(marker) = syntax.multiline_string {
+            if rest.starts_with(marker) {
+                let body = index + marker.len();
+                let Some(end) = line[body..].find(marker) else {
+                    tokens.push(token(index..line.len(), TokenKind::String));
+                    return (tokens, MULTILINE_STRING);
+                };
+                let end = body + end + marker.len();
+                tokens.push(token(index..end, TokenKind::String));
+                index = end;
+                continue;
+            }
+        }
+
+        let Some(char) = rest.chars().next() else {
+            break;
+        };
+
+        if char == '"' || (char == '\'' && syntax.single_quote_strings) {
+            let end = string_end(line, index, char).unwrap_or(line.len());
+            let kind = if syntax.keys && is_key(line, end) {
+                TokenKind::Key
+            } else {
+                TokenKind::String
+            };
+            tokens.push(token(index..end, kind));
+            index = end;
+            continue;
+        }
+
+        if char == '\'' {
+            // Char literal like 'a' or '\n', otherwise a lifetime
+            let end = string_end(line, index, '\'').filter(|end| end - index <= 4 + 2);
+            if let Some(end) = end.filter(|end| line[index..*end].chars().count() <= 4) {
+                tokens.push(token(index..end, TokenKind::String));
+                index = end;
+                continue;
+            }
+        }
+
+        if char.is_ascii_digit()
+            || (char == '-'
+                && syntax.keys
+                && rest[1..].starts_with(|next: char| next.is_ascii_digit()))
+        {
+            let end = rest[1..]
+                .find(|next: char| !(next.is_ascii_alphanumeric() || matches!(next, '.' | '_')))
+                .map_or(line.len(), |end| index + 1 + end);
+            tokens.push(token(index..end, TokenKind::Number));
+            index = end;
+            continue;
+        }
+
+        if char.is_alphabetic() || char == '_' {
+            let end = rest
+                .find(|next: char| !(next.is_alphanumeric() || matches!(next, '_' | '-')))
+                .map_or(line.len(), |end| index + end);
+            let word = &line[index..end];
+            let kind = if syntax.keywords.contains(&word) {
+                Some(TokenKind::Keyword)
+            } else if syntax.literals.contains(&word) {
+                Some(TokenKind::Literal)
+            } else if syntax.keys && is_key(line, end) {
+                Some(TokenKind::Key)
+            } else if syntax.types && word.starts_with(char::is_uppercase) {
+                Some(TokenKind::Type)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                tokens.push(token(index..end, kind));
+            }
+            index = end;
+            continue;
+        }
+
+        index += char.len_utf8();
+    }
+
+    (tokens, NORMAL)
+}
+
+fn highlight_markdown(line: &str, state: LineState) -> (Vec<Token>, LineState) {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+        let state = if state == CODE_BLOCK {
+            NORMAL
+        } else {
+            CODE_BLOCK
+        };
+        return (vec![token(0..line.len(), TokenKind::Code)], state);
+    }
+    if state == CODE_BLOCK {
+        return (vec![token(0..line.len(), TokenKind::Code)], CODE_BLOCK);
+    }
+    if trimmed.starts_with('#') {
+        return (vec![token(0..line.len(), TokenKind::Heading)], NORMAL);
+    }
+    if trimmed.starts_with('>') {
+        return (vec![token(0..line.len(), TokenKind::Comment)], NORMAL);
+    }
+
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < line.len() {
+        let rest = &line[index..];
+        let span = match rest.as_bytes()[0] {
+            b'`' => rest[1..].find('`').map(|end| (end + 2, TokenKind::Code)),
+            b'*' | b'_' if rest[1..].starts_with(&rest[..1]) => rest[2..]
+                .find(&rest[..2])
+                .map(|end| (end + 4, TokenKind::Emphasis)),
+            b'*' | b'_' => rest[1..]
+                .find(&rest[..1])
+                .filter(|end| *end > 0)
+                .map(|end| (end + 2, TokenKind::Emphasis)),
+            b'[' if rest[1..].starts_with('[') => {
+                rest.find("]]").map(|end| (end + 2, TokenKind::Link))
+            }
+            b'[' => rest
+                .find("](")
+                .and_then(|middle| rest[middle..].find(')').map(|end| middle + end + 1))
+                .map(|end| (end, TokenKind::Link)),
+            _ => None,
+        };
+
+        if let Some((length, kind)) = span {
+            tokens.push(token(index..index + length, kind));
+            index += length;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    (tokens, NORMAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn highlighted(file: &str, lines: &[&str]) -> Vec<Vec<(String, TokenKind)>> {
+        let mut state = LineState::default();
+        lines
+            .iter()
+            .map(|line| {
+                let (tokens, next) = BasicHighlighter.highlight_line(Path::new(file), line, state);
+                state = next;
+                tokens
+                    .into_iter()
+                    .map(|token| (line[token.range].to_string(), token.kind))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn owned(tokens: &[(&str, TokenKind)]) -> Vec<(String, TokenKind)> {
+        tokens
+            .iter()
+            .map(|(text, kind)| ((*text).to_string(), *kind))
+            .collect()
+    }
+
+    #[test]
+    fn rust() {
+        let result = highlighted(
+            "main.rs",
+            &[
+                "pub fn main() -> Result<(), Error> { // entry",
+                "    let c = 'x'; /* multi",
+                "    line */ println!(\"{c} 42\", 42);",
+            ],
+        );
+        assert_eq!(
+            result,
+            [
+                owned(&[
+                    ("pub", TokenKind::Keyword),
+                    ("fn", TokenKind::Keyword),
+                    ("Result", TokenKind::Type),
+                    ("Error", TokenKind::Type),
+                    ("// entry", TokenKind::Comment),
+                ]),
+                owned(&[
+                    ("let", TokenKind::Keyword),
+                    ("'x'", TokenKind::String),
+                    ("/* multi", TokenKind::Comment),
+                ]),
+                owned(&[
+                    ("    line */", TokenKind::Comment),
+                    ("\"{c} 42\"", TokenKind::String),
+                    ("42", TokenKind::Number),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn toml() {
+        let result = highlighted(
+            "Cargo.toml",
+            &["[package]", "name = \"ki\" # the name", "edition = 2024"],
+        );
+        assert_eq!(
+            result,
+            [
+                owned(&[("[package]", TokenKind::Heading)]),
+                owned(&[
+                    ("name", TokenKind::Key),
+                    ("\"ki\"", TokenKind::String),
+                    ("# the name", TokenKind::Comment),
+                ]),
+                owned(&[("edition", TokenKind::Key), ("2024", TokenKind::Number)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn json() {
+        let result = highlighted("data.json", &["{\"a\": [true, null, -1.5, \"b\"]}"]);
+        assert_eq!(
+            result,
+            [owned(&[
+                ("\"a\"", TokenKind::Key),
+                ("true", TokenKind::Literal),
+                ("null", TokenKind::Literal),
+                ("-1.5", TokenKind::Number),
+                ("\"b\"", TokenKind::String),
+            ])]
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let result = highlighted(
+            "notes.md",
+            &[
+                "# Title",
+                "Some *emphasis* and `code`, see [[Other]]",
+                "```rust",
+                "let a = 1;",
+                "```",
+            ],
+        );
+        assert_eq!(
+            result,
+            [
+                owned(&[("# Title", TokenKind::Heading)]),
+                owned(&[
+                    ("*emphasis*", TokenKind::Emphasis),
+                    ("`code`", TokenKind::Code),
+                    ("[[Other]]", TokenKind::Link),
+                ]),
+                owned(&[("```rust", TokenKind::Code)]),
+                owned(&[("let a = 1;", TokenKind::Code)]),
+                owned(&[("```", TokenKind::Code)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_language_is_not_highlighted() {
+        assert_eq!(highlighted("notes.txt", &["fn main() {}"]), [Vec::new()]);
+    }
+}
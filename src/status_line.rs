@@ -0,0 +1,262 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use std::path::Path;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Widget;
+
+use crate::explorer::Explorer;
+use crate::explorer::state::ExplorerState;
+use crate::fs::Metadata;
+
+/// A part of a [`StatusLine`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// Position of the selected node among the visible nodes, like `42/1380`
+    Position,
+    /// Amount of entries of the explorer, like `1380 items`
+    Count,
+    /// File name of the selected node
+    SelectedName,
+    /// Size of the selected file, like `1.5 KiB`. Empty for directories.
+    SelectedSize,
+    /// Amount of expanded directories, like `3 expanded`
+    Expanded,
+    /// Custom text, like the current filter of the app
+    Text(String),
+}
+
+/// A footer summarizing an [`Explorer`] and its [`ExplorerState`].
+///
+/// Renders [`Segment`]s aligned to the left and to the right, joined by a separator.
+/// Empty segments are left out.
+#[derive(Debug, Clone)]
+pub struct StatusLine<'explorer, 'text, T>
+where
+    T: AsRef<Path> + Clone + Eq + PartialEq + Ord + Hash + Debug,
+{
+    explorer: &'explorer Explorer<'text, T>,
+    state: &'explorer ExplorerState<T>,
+
+    left: Vec<Segment>,
+    right: Vec<Segment>,
+    separator: String,
+
+    /// Style used as a base style for the widget
+    style: Style,
+    separator_style: Style,
+}
+
+impl<'explorer, 'text, T> StatusLine<'explorer, 'text, T>
+where
+    T: AsRef<Path> + Clone + Eq + PartialEq + Ord + Hash + Debug,
+{
+    /// Create a new `StatusLine` showing the position and name of the selected node on the left
+    /// and the amount of entries on the right.
+    #[must_use]
+    pub fn new(
+        explorer: &'explorer Explorer<'text, T>,
+        state: &'explorer ExplorerState<T>,
+    ) -> Self {
+        Self {
+            explorer,
+            state,
+            left: vec![Segment::Position, Segment::SelectedName],
+            right: vec![Segment::Count],
+            separator: " \u{2502} ".to_string(),
+            style: Style::new(),
+            separator_style: Style::new(),
+        }
+    }
+
+    /// Segments aligned to the left.
+    #[must_use]
+    pub fn left(mut self, segments: Vec<Segment>) -> Self {
+        self.left = segments;
+        self
+    }
+
+    /// Segments aligned to the right.
+    #[must_use]
+    pub fn right(mut self, segments: Vec<Segment>) -> Self {
+        self.right = segments;
+        self
+    }
+
+    #[must_use]
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    #[must_use]
+    pub const fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn separator_style(mut self, style: Style) -> Self {
+        self.separator_style = style;
+        self
+    }
+
+    fn segment_text(&self, segment: &Segment) -> String {
+        let selected = self.state.selected().last();
+        match segment {
+            Segment::Position => {
+                let position = self.state.selected_position().map_or(0, |index| index + 1);
+                format!("{position}/{}", self.state.last_identifiers.len())
+            }
+            Segment::Count => format!("{} items", self.explorer.entries.len()),
+            Segment::SelectedName => selected
+                .and_then(|identifier| identifier.as_ref().file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Segment::SelectedSize => selected
+                .and_then(|identifier| self.explorer.fs.metadata(identifier.as_ref()).ok())
+                .filter(Metadata::is_file)
+                .map(|metadata| human_size(metadata.len))
+                .unwrap_or_default(),
+            Segment::Expanded => format!("{} expanded", self.state.expanded().len()),
+            Segment::Text(text) => text.clone(),
+        }
+    }
+
+    /// Join the non-empty segments which fit into `width`.
+    ///
+    /// A segment which does not fit is cut, but a separator is only drawn when it fits along
+    /// with at least one character of the following segment.
+    fn line(&self, segments: &[Segment], width: usize) -> Line<'_> {
+        let separator_width = Span::raw(self.separator.as_str()).width();
+        let mut spans = Vec::new();
+        let mut remaining = width;
+        let texts = segments
+            .iter()
+            .map(|segment| self.segment_text(segment))
+            .filter(|text| !text.is_empty());
+        for text in texts {
+            if !spans.is_empty() {
+                if remaining <= separator_width {
+                    break;
+                }
+                spans.push(Span::styled(self.separator.as_str(), self.separator_style));
+                remaining -= separator_width;
+            }
+            let span = Span::raw(text);
+            remaining = remaining.saturating_sub(span.width());
+            spans.push(span);
+        }
+        Line::from(spans)
+    }
+}
+
+impl<T> Widget for StatusLine<'_, '_, T>
+where
+    T: AsRef<Path> + Clone + Eq + PartialEq + Ord + Hash + Debug,
+{
+    #[expect(clippy::cast_possible_truncation)]
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        if area.width < 1 || area.height < 1 {
+            return;
+        }
+
+        let right = self.line(&self.right, usize::from(area.width));
+        let right_width = (right.width() as u16).min(area.width);
+        buf.set_line(area.right() - right_width, area.y, &right, right_width);
+
+        // The left segments get what is left, keeping a gap to the right segments
+        let gap = u16::from(right_width > 0);
+        let left_width = area.width.saturating_sub(right_width + gap);
+        let left = self.line(&self.left, usize::from(left_width));
+        buf.set_line(area.x, area.y, &left, left_width);
+    }
+}
+
+/// Format a size in bytes like `1.5 KiB`.
+#[must_use]
+#[expect(clippy::cast_precision_loss)]
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human_size(12), "12 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn segments_left_and_right() {
+        let root = PathBuf::from("/ki-status-line");
+        let mut explorer = Explorer::new("status", &root).unwrap();
+        explorer
+            .add_entries([root.join("a.rs"), root.join("b.rs"), root.join("c.rs")])
+            .unwrap();
+
+        let mut state = ExplorerState {
+            last_identifiers: explorer
+                .entries
+                .iter()
+                .map(|path| vec![path.clone()])
+                .collect(),
+            ..ExplorerState::default()
+        };
+        state.select(vec![root.join("b.rs")]);
+
+        let status_line = StatusLine::new(&explorer, &state)
+            .separator(" | ")
+            .right(vec![
+                Segment::Text("sort: name".to_string()),
+                Segment::Count,
+            ]);
+        let area = Rect::new(0, 0, 40, 1);
+        let mut buffer = Buffer::empty(area);
+        status_line.clone().render(area, &mut buffer);
+        assert_eq!(
+            buffer,
+            Buffer::with_lines(["2/3 | b.rs          sort: name | 3 items"])
+        );
+
+        // The right segments win when space runs out, without leaving a dangling separator
+        let area = Rect::new(0, 0, 26, 1);
+        let mut buffer = Buffer::empty(area);
+        status_line.clone().render(area, &mut buffer);
+        assert_eq!(buffer, Buffer::with_lines(["2/3   sort: name | 3 items"]));
+
+        // The left segments are cut before a separator which does not fit
+        for (width, left) in [(27, "2/3    "), (28, "2/3 | b "), (30, "2/3 | b.r ")] {
+            let area = Rect::new(0, 0, width, 1);
+            let mut buffer = Buffer::empty(area);
+            status_line.clone().render(area, &mut buffer);
+            assert_eq!(
+                buffer,
+                Buffer::with_lines([format!("{left}sort: name | 3 items")])
+            );
+        }
+    }
+}
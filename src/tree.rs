@@ -1,22 +1,26 @@
+use core::fmt::Debug;
 use core::hash::Hash;
 use std::collections::HashSet;
-use std::fmt::Debug;
-use std::path::Path;
 
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
 use ratatui::widgets::{
-    Block, Scrollbar, ScrollbarState, StatefulWidget, StatefulWidgetRef, Widget,
+    Block, Clear, Scrollbar, ScrollbarState, StatefulWidget as _, StatefulWidgetRef, Widget as _,
 };
 use unicode_width::UnicodeWidthStr as _;
 
-use super::tree::flatten::Flattened;
+use super::tree::flatten::{COMPACT_SEPARATOR, Flattened, flatten_compact};
 use crate::explorer::state::ExplorerState;
 use crate::tree::item::TreeItem;
+pub use crate::tree::truncate::Truncation;
+use crate::tree::truncate::skip_columns;
 
 pub(super) mod flatten;
-pub(super) mod item;
+pub mod item;
+pub mod source;
+pub mod truncate;
 
 /// TODO
 #[derive(Debug, Clone, PartialEq)]
@@ -26,6 +30,9 @@ pub struct Tree<'text, Identifier> {
     /// Explorer block
     block: Option<Block<'static>>,
     scrollbar: Option<Scrollbar<'static>>,
+    horizontal_scrollbar: Option<Scrollbar<'static>>,
+    /// Scroll horizontally to keep the text of a newly selected item visible
+    horizontal_auto_scroll: bool,
 
     /// Style used as a base style for the widget
     style: Style,
@@ -40,6 +47,17 @@ pub struct Tree<'text, Identifier> {
     node_open_symbol: String,
     /// Symbol displayed in front of a node without children.
     node_no_children_symbol: String,
+
+    /// How item text is shortened when it does not fit its row
+    truncation: Truncation,
+    /// Style of the line showing the full text of a truncated selected item, `None` to hide it
+    full_name_style: Option<Style>,
+    /// Merge chains of single child folders into one row
+    compact_folders: bool,
+    /// Maximum amount of ancestor rows pinned to the top while scrolling, `0` to disable
+    sticky_headers: usize,
+    /// Style patched onto pinned ancestor rows
+    sticky_header_style: Style,
 }
 
 impl<'text, Identifier> Tree<'text, Identifier>
@@ -67,68 +85,163 @@ where
             items,
             block: None,
             scrollbar: None,
+            horizontal_scrollbar: None,
+            horizontal_auto_scroll: false,
             style: Style::new(),
             highlight_style: Style::new().add_modifier(Modifier::REVERSED),
             highlight_symbol: String::new(),
             node_closed_symbol: "\u{25b6} ".to_string(),
             node_open_symbol: "\u{25bc} ".to_string(),
             node_no_children_symbol: "  ".to_string(),
+            truncation: Truncation::default(),
+            full_name_style: None,
+            compact_folders: false,
+            sticky_headers: 0,
+            sticky_header_style: Style::new(),
         })
     }
 
+    /// Replace the items while keeping the configuration of this `Tree`.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there are duplicate identifiers in the items.
+    pub fn set_items(&mut self, items: Vec<TreeItem<'text, Identifier>>) -> std::io::Result<()> {
+        self.items = Self::new(items)?.items;
+        Ok(())
+    }
+
+    #[must_use]
     pub fn block(mut self, block: Block<'static>) -> Self {
         self.block = Some(block);
         self
     }
 
     /// Show the scrollbar when rendering this widget.
-    pub fn experimental_scrollbar(mut self, scrollbar: Option<Scrollbar<'static>>) -> Self {
+    #[must_use]
+    pub const fn experimental_scrollbar(mut self, scrollbar: Option<Scrollbar<'static>>) -> Self {
         self.scrollbar = scrollbar;
         self
     }
 
-    pub fn style(mut self, style: Style) -> Self {
+    /// Show the horizontal scrollbar when rendering this widget.
+    #[must_use]
+    pub const fn experimental_horizontal_scrollbar(
+        mut self,
+        scrollbar: Option<Scrollbar<'static>>,
+    ) -> Self {
+        self.horizontal_scrollbar = scrollbar;
+        self
+    }
+
+    /// Scroll horizontally so the text of the selected item stays visible after it was selected,
+    /// even when it is nested deeper than the width of the area allows.
+    #[must_use]
+    pub const fn horizontal_auto_scroll(mut self, auto_scroll: bool) -> Self {
+        self.horizontal_auto_scroll = auto_scroll;
+        self
+    }
+
+    #[must_use]
+    pub const fn style(mut self, style: Style) -> Self {
         self.style = style;
         self
     }
 
-    pub fn highlight_style(mut self, style: Style) -> Self {
+    #[must_use]
+    pub const fn highlight_style(mut self, style: Style) -> Self {
         self.highlight_style = style;
         self
     }
 
+    #[must_use]
     pub fn highlight_symbol(mut self, highlight_symbol: &str) -> Self {
         self.highlight_symbol = highlight_symbol.to_string();
         self
     }
 
+    #[must_use]
     pub fn node_closed_symbol(mut self, symbol: &str) -> Self {
         self.node_closed_symbol = symbol.to_string();
         self
     }
 
+    #[must_use]
     pub fn node_open_symbol(mut self, symbol: &str) -> Self {
         self.node_open_symbol = symbol.to_string();
         self
     }
 
+    #[must_use]
     pub fn node_no_children_symbol(mut self, symbol: &str) -> Self {
         self.node_no_children_symbol = symbol.to_string();
         self
     }
 
-    pub fn items(&self) -> &Vec<TreeItem<Identifier>> {
+    /// How item text is shortened when it is wider than its row.
+    /// Defaults to [`Truncation::Clip`].
+    #[must_use]
+    pub const fn truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Show the full text of the selected item in the last row of the area
+    /// whenever it does not fit into its own row.
+    #[must_use]
+    pub const fn full_name_line(mut self, style: Option<Style>) -> Self {
+        self.full_name_style = style;
+        self
+    }
+
+    /// Merge chains of folders which only contain a single folder into one row,
+    /// like `main/java/com/acme`.
+    ///
+    /// Expanding and collapsing the row uses the identifier of the innermost folder.
+    /// Every folder of the chain is still selectable on its own, the selected one is highlighted.
+    #[must_use]
+    pub const fn compact_folders(mut self, compact_folders: bool) -> Self {
+        self.compact_folders = compact_folders;
+        self
+    }
+
+    /// Pin the rows of the ancestors of the first item below them to the top of the area
+    /// while scrolling, like sticky scroll in editors. Stacks up to `max_depth` rows.
+    ///
+    /// Clicking a pinned row with [`ExplorerState::click_at`] selects and scrolls to it.
+    #[must_use]
+    pub const fn sticky_headers(mut self, max_depth: usize) -> Self {
+        self.sticky_headers = max_depth;
+        self
+    }
+
+    /// Style patched onto the rows pinned by [`sticky_headers`](Self::sticky_headers).
+    #[must_use]
+    pub const fn sticky_header_style(mut self, style: Style) -> Self {
+        self.sticky_header_style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn items(&self) -> &Vec<TreeItem<'text, Identifier>> {
         &self.items
     }
+
+    /// Change the items in place, like adding the entries of a scan as they arrive.
+    ///
+    /// The identifiers of siblings have to stay unique, see [`TreeItem::add_child`].
+    pub const fn items_mut(&mut self) -> &mut Vec<TreeItem<'text, Identifier>> {
+        &mut self.items
+    }
 }
 
 impl<Identifier> StatefulWidgetRef for Tree<'_, Identifier>
 where
-    Identifier: AsRef<Path> + Clone + PartialEq + Eq + Hash + Debug,
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
 {
     type State = ExplorerState<Identifier>;
 
-    #[allow(clippy::too_many_lines)]
+    #[expect(clippy::too_many_lines)]
     fn render_ref(&self, full_area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         buf.set_style(full_area, self.style);
 
@@ -141,23 +254,88 @@ where
 
         state.last_area = area;
         state.last_rendered_identifiers.clear();
+        state.last_rendered_segments.clear();
         if area.width < 1 || area.height < 1 {
             return;
         }
 
-        let visible = state.flatten(self.items.clone());
+        let visible = if self.compact_folders {
+            flatten_compact(&state.expanded, &self.items, &[])
+        } else {
+            state.flatten(self.items.clone())
+        };
         state.last_biggest_index = visible.len().saturating_sub(1);
         if visible.is_empty() {
             return;
         }
 
+        let blank_symbol = " ".repeat(self.highlight_symbol.width());
+        let has_selection = !state.selected.is_empty();
+
+        // Columns available for indentation, node symbol and text
+        let viewport = if has_selection {
+            (area.width as usize).saturating_sub(self.highlight_symbol.width())
+        } else {
+            area.width as usize
+        };
+        state.last_content_viewport = viewport;
+        state.last_content_width = visible
+            .iter()
+            .map(|flattened| self.prefix_width(flattened, state) + flattened.text().width())
+            .max()
+            .unwrap_or_default();
+
+        if self.horizontal_auto_scroll && state.ensure_selected_in_view_on_next_render {
+            if let Some(flattened) = visible
+                .iter()
+                .find(|flattened| flattened.identifier == state.selected)
+            {
+                let text_start = self.prefix_width(flattened, state);
+                let text_width = flattened.text().width().min(viewport);
+                state.horizontal_offset = state
+                    .horizontal_offset
+                    .min(text_start)
+                    .max((text_start + text_width).saturating_sub(viewport));
+            }
+        }
+        state.horizontal_offset = state
+            .horizontal_offset
+            .min(state.last_content_width.saturating_sub(viewport));
+
+        // The full name of the selected item when it does not fit its row
+        let full_name = self.full_name_style.and_then(|style| {
+            let flattened = visible
+                .iter()
+                .find(|flattened| flattened.identifier == state.selected)?;
+            let prefix_width = self.prefix_width(flattened, state);
+            let is_cut_at_start = state.horizontal_offset > prefix_width;
+            let row_width =
+                prefix_width.saturating_sub(state.horizontal_offset) + flattened.text().width();
+            (is_cut_at_start || row_width > viewport).then(|| {
+                let text = flattened.text();
+                let spans = text
+                    .lines
+                    .iter()
+                    .flat_map(|line| line.spans.iter().cloned());
+                Line::from(spans.collect::<Vec<_>>()).style(style)
+            })
+        });
+        let area = if full_name.is_some() {
+            Rect {
+                height: area.height.saturating_sub(1),
+                ..area
+            }
+        } else {
+            area
+        };
+
         let available_height = area.height as usize;
 
         let ensure_index_in_view =
             if state.ensure_selected_in_view_on_next_render && !state.selected.is_empty() {
                 visible
                     .iter()
-                    .position(|flattened| flattened.identifier == state.selected)
+                    .position(|flattened| flattened.identifier.starts_with(&state.selected))
             } else {
                 None
             };
@@ -166,7 +344,11 @@ where
         let mut start = state.offset.min(state.last_biggest_index);
 
         if let Some(ensure_index_in_view) = ensure_index_in_view {
-            start = start.min(ensure_index_in_view);
+            // Keep room for the sticky headers above so they do not cover the selected item
+            let headers = visible[ensure_index_in_view]
+                .depth()
+                .min(self.sticky_headers);
+            start = start.min(ensure_index_in_view.saturating_sub(headers));
         }
 
         let mut end = start;
@@ -212,13 +394,28 @@ where
             scrollbar.render(scrollbar_area, buf, &mut scrollbar_state);
         }
 
-        let blank_symbol = " ".repeat(self.highlight_symbol.width());
+        if let Some(scrollbar) = self.horizontal_scrollbar.clone() {
+            let mut scrollbar_state =
+                ScrollbarState::new(state.last_content_width.saturating_sub(viewport))
+                    .position(state.horizontal_offset)
+                    .viewport_content_length(viewport);
+            let scrollbar_area = Rect {
+                // Inner width to be exactly as the content
+                x: area.x,
+                width: area.width,
+                // Outer height to stay on the bottom border
+                y: full_area.y,
+                height: full_area.height,
+            };
+            scrollbar.render(scrollbar_area, buf, &mut scrollbar_state);
+        }
 
         let mut current_height = 0;
-        let has_selection = !state.selected.is_empty();
-        #[allow(clippy::cast_possible_truncation)]
+        #[expect(clippy::cast_possible_truncation)]
         for flattened in visible.iter().skip(state.offset).take(end - start) {
-            let Flattened { identifier, item } = flattened;
+            let Flattened {
+                identifier, item, ..
+            } = flattened;
             let x = area.x;
             let y = area.y + current_height;
             let height = item.height() as u16;
@@ -231,50 +428,49 @@ where
                 height,
             };
 
-            let text = &item.text;
-            let item_style = text.style;
-
             let is_selected = state.selected == *identifier;
-            let after_highlight_symbol_x = if has_selection {
-                let symbol = if is_selected {
-                    &self.highlight_symbol
-                } else {
-                    &blank_symbol
-                };
-                let (x, _) = buf.set_stringn(x, y, symbol, area.width as usize, item_style);
-                x
+            // A folder merged into this row by compact folders is selected
+            let is_segment_selected = !is_selected
+                && flattened
+                    .segments()
+                    .any(|(segment, _)| segment == state.selected.as_slice());
+            let highlight_symbol = if !has_selection {
+                ""
+            } else if is_selected || is_segment_selected {
+                &self.highlight_symbol
             } else {
-                x
-            };
-
-            let after_depth_x = {
-                let indent_width = flattened.depth() * 2;
-                let (after_indent_x, _) = buf.set_stringn(
-                    after_highlight_symbol_x,
-                    y,
-                    " ".repeat(indent_width),
-                    indent_width,
-                    item_style,
-                );
-                let symbol = if item.children.is_empty() {
-                    &self.node_no_children_symbol
-                } else if state.expanded.contains(identifier) {
-                    &self.node_open_symbol
-                } else {
-                    &self.node_closed_symbol
-                };
-                let max_width = area.width.saturating_sub(after_indent_x - x);
-                let (x, _) =
-                    buf.set_stringn(after_indent_x, y, symbol, max_width as usize, item_style);
-                x
+                &blank_symbol
             };
-
-            let text_area = Rect {
-                x: after_depth_x,
-                width: area.width.saturating_sub(after_depth_x - x),
-                ..area
-            };
-            text.render(text_area, buf);
+            let (text_area, text_skip) =
+                self.render_row(flattened, area, buf, state, highlight_symbol);
+
+            if !flattened.compacted.is_empty() {
+                let mut column = 0_usize;
+                for (segment, width) in flattened.segments() {
+                    let visible_width = text_area.width as usize;
+                    let segment_start = column.saturating_sub(text_skip).min(visible_width);
+                    let segment_end = (column + width)
+                        .saturating_sub(text_skip)
+                        .min(visible_width);
+                    column += width + COMPACT_SEPARATOR.width();
+                    if segment_start == segment_end {
+                        continue;
+                    }
+
+                    let segment_area = Rect {
+                        x: text_area.x + segment_start as u16,
+                        width: (segment_end - segment_start) as u16,
+                        height: 1,
+                        ..text_area
+                    };
+                    if is_segment_selected && segment == state.selected.as_slice() {
+                        buf.set_style(segment_area, self.highlight_style);
+                    }
+                    state
+                        .last_rendered_segments
+                        .push((segment_area, segment.to_vec()));
+                }
+            }
 
             if is_selected {
                 buf.set_style(area, self.highlight_style);
@@ -285,6 +481,19 @@ where
                 .push((area.y, identifier.clone()));
         }
 
+        if self.sticky_headers > 0 {
+            self.render_sticky_headers(&visible, area, buf, state, &blank_symbol);
+        }
+
+        if let Some(full_name) = full_name {
+            buf.set_line(area.x, area.bottom(), &full_name, area.width);
+        }
+
+        state.last_parents = visible
+            .iter()
+            .filter(|flattened| !flattened.item.children().is_empty())
+            .map(|flattened| flattened.identifier.clone())
+            .collect();
         state.last_identifiers = visible
             .into_iter()
             .map(|flattened| flattened.identifier)
@@ -292,18 +501,191 @@ where
     }
 }
 
+impl<Identifier> Tree<'_, Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
+{
+    /// Symbol in front of the item text depending on its children and whether it is expanded.
+    fn node_symbol(
+        &self,
+        flattened: &Flattened<'_, Identifier>,
+        state: &ExplorerState<Identifier>,
+    ) -> &str {
+        if flattened.item.children.is_empty() {
+            &self.node_no_children_symbol
+        } else if state.expanded.contains(&flattened.identifier) {
+            &self.node_open_symbol
+        } else {
+            &self.node_closed_symbol
+        }
+    }
+
+    /// Render the highlight symbol, indentation, node symbol and text of a row.
+    ///
+    /// Returns the area of the text and the amount of its columns scrolled out of view.
+    fn render_row(
+        &self,
+        flattened: &Flattened<'_, Identifier>,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &ExplorerState<Identifier>,
+        highlight_symbol: &str,
+    ) -> (Rect, usize) {
+        let Rect { x, y, .. } = area;
+        let text = flattened.text();
+        let item_style = text.style;
+
+        let (after_highlight_symbol_x, _) =
+            buf.set_stringn(x, y, highlight_symbol, area.width as usize, item_style);
+
+        let after_depth_x = {
+            let indent = " ".repeat(flattened.depth() * 2);
+            let symbol = self.node_symbol(flattened, state);
+            let prefix = Line::styled(format!("{indent}{symbol}"), item_style);
+            let prefix = skip_columns(&prefix, state.horizontal_offset);
+            let max_width = area.width.saturating_sub(after_highlight_symbol_x - x);
+            let (x, _) = buf.set_line(after_highlight_symbol_x, y, &prefix, max_width);
+            x
+        };
+
+        let text_area = Rect {
+            x: after_depth_x,
+            width: area.width.saturating_sub(after_depth_x - x),
+            ..area
+        };
+        let text_skip = state
+            .horizontal_offset
+            .saturating_sub(self.prefix_width(flattened, state));
+        // Scrolled horizontally the part of the text in view is truncated
+        let mut text = text.into_owned();
+        for line in &mut text.lines {
+            *line = skip_columns(line, text_skip);
+        }
+        self.truncation
+            .truncate_text(&text, text_area.width as usize)
+            .render(text_area, buf);
+
+        (text_area, text_skip)
+    }
+
+    /// Pin the ancestors of the topmost visible rows to the top of the area,
+    /// covering the rows rendered there.
+    #[expect(clippy::cast_possible_truncation)]
+    fn render_sticky_headers(
+        &self,
+        visible: &[Flattened<'_, Identifier>],
+        area: Rect,
+        buf: &mut Buffer,
+        state: &mut ExplorerState<Identifier>,
+        blank_symbol: &str,
+    ) {
+        let max_headers = self
+            .sticky_headers
+            .min(area.height.saturating_sub(1) as usize);
+
+        // The outermost ancestors of a row which are above it, hidden behind the headers or
+        // scrolled out
+        let ancestors = |index: usize| {
+            let Some(row) = visible.get(index) else {
+                return Vec::new();
+            };
+            let mut ancestors = visible[..index]
+                .iter()
+                .rev()
+                .filter(|flattened| {
+                    flattened.identifier.len() < row.identifier.len()
+                        && row.identifier.starts_with(&flattened.identifier)
+                })
+                .take(row.depth())
+                .collect::<Vec<_>>();
+            ancestors.reverse();
+            ancestors.truncate(max_headers);
+            ancestors
+        };
+
+        // Pin as many rows as the first row below them has ancestors. Where that row just left
+        // a folder, the folder of the row covered last stays pinned instead.
+        let mut count = 0;
+        while ancestors(state.offset + count).len() > count {
+            count += 1;
+        }
+        if count == 0 {
+            return;
+        }
+        let mut headers = ancestors(state.offset + count - 1);
+        headers.truncate(count);
+
+        let covered_until = area.y + headers.len() as u16;
+        state
+            .last_rendered_identifiers
+            .retain(|(y, _)| *y >= covered_until);
+        state
+            .last_rendered_segments
+            .retain(|(segment_area, _)| segment_area.y >= covered_until);
+
+        let has_selection = !state.selected.is_empty();
+        for (index, header) in headers.into_iter().enumerate() {
+            let row = Rect {
+                y: area.y + index as u16,
+                height: 1,
+                ..area
+            };
+            Clear.render(row, buf);
+            buf.set_style(row, self.style);
+
+            let is_selected = state.selected == header.identifier;
+            let highlight_symbol = if !has_selection {
+                ""
+            } else if is_selected {
+                &self.highlight_symbol
+            } else {
+                blank_symbol
+            };
+            self.render_row(header, row, buf, state, highlight_symbol);
+            buf.set_style(row, self.sticky_header_style);
+            if is_selected {
+                buf.set_style(row, self.highlight_style);
+            }
+
+            state
+                .last_rendered_identifiers
+                .insert(index, (row.y, header.identifier.clone()));
+        }
+    }
+
+    /// Width of the indentation and node symbol in front of the item text.
+    fn prefix_width(
+        &self,
+        flattened: &Flattened<'_, Identifier>,
+        state: &ExplorerState<Identifier>,
+    ) -> usize {
+        flattened.depth() * 2 + self.node_symbol(flattened, state).width()
+    }
+}
+
 #[cfg(test)]
 mod render_tests {
     use super::*;
+    use ratatui::layout::Position;
 
     #[must_use]
     #[track_caller]
     fn render(width: u16, height: u16, state: &mut ExplorerState<&'static str>) -> Buffer {
-        let items = TreeItem::example();
-        let tree = Tree::new(items).unwrap();
+        let tree = Tree::new(TreeItem::example()).unwrap();
+        render_tree(&tree, width, height, state)
+    }
+
+    #[must_use]
+    #[track_caller]
+    fn render_tree(
+        tree: &Tree<&'static str>,
+        width: u16,
+        height: u16,
+        state: &mut ExplorerState<&'static str>,
+    ) -> Buffer {
         let area = Rect::new(0, 0, width, height);
         let mut buffer = Buffer::empty(area);
-        StatefulWidgetRef::render_ref(&tree, area, &mut buffer, state);
+        StatefulWidgetRef::render_ref(tree, area, &mut buffer, state);
         buffer
     }
 
@@ -364,4 +746,256 @@ mod render_tests {
         ]);
         assert_eq!(buffer, expected);
     }
+
+    #[test]
+    fn truncated_with_full_name_line() {
+        let tree = Tree::new(TreeItem::example())
+            .unwrap()
+            .highlight_style(Style::new())
+            .truncation(Truncation::End)
+            .full_name_line(Some(Style::new()));
+        let mut state = ExplorerState::default();
+        state.select(vec!["h"]);
+        let buffer = render_tree(&tree, 6, 4, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "  Alfa",
+            "▶ Bra…",
+            "  Hot…",
+            "Hotel ",
+        ]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn truncated_when_scrolled_horizontally() {
+        let items = vec![TreeItem::new_leaf("a", "Alfa Bravo Charlie")];
+        let tree = Tree::new(items).unwrap().truncation(Truncation::End);
+        let mut state = ExplorerState {
+            horizontal_offset: 4,
+            ..ExplorerState::default()
+        };
+        let buffer = render_tree(&tree, 8, 1, &mut state);
+        assert_eq!(buffer, Buffer::with_lines(["fa Brav…"]));
+    }
+
+    #[test]
+    fn full_name_line_hidden_when_selected_fits() {
+        let tree = Tree::new(TreeItem::example())
+            .unwrap()
+            .highlight_style(Style::new())
+            .full_name_line(Some(Style::new()));
+        let mut state = ExplorerState::default();
+        state.select(vec!["a"]);
+        let buffer = render_tree(&tree, 6, 4, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "  Alfa",
+            "▶ Brav",
+            "  Hote",
+            "      ",
+        ]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn scrolled_horizontally() {
+        let mut state = ExplorerState::default();
+        state.expand(vec!["b"]);
+        state.expand(vec!["b", "d"]);
+        state.horizontal_offset = 2;
+        let buffer = render(8, 5, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "Alfa    ",
+            "Bravo   ",
+            "  Charli",
+            "▼ Delta ",
+            "    Echo",
+        ]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn horizontal_auto_scroll_follows_selection() {
+        let tree = Tree::new(TreeItem::example())
+            .unwrap()
+            .horizontal_auto_scroll(true);
+        let mut state = ExplorerState::default();
+        state.expand(vec!["b"]);
+        state.expand(vec!["b", "d"]);
+        state.select(vec!["b", "d", "e"]);
+        _ = render_tree(&tree, 6, 8, &mut state);
+        assert_eq!(state.horizontal_offset, 4);
+
+        state.select(vec!["a"]);
+        _ = render_tree(&tree, 6, 8, &mut state);
+        assert_eq!(state.horizontal_offset, 2);
+
+        assert!(state.scroll_right(100));
+        assert_eq!(state.horizontal_offset, 7);
+        assert!(state.scroll_left(100));
+        assert!(!state.scroll_left(1));
+    }
+
+    #[test]
+    fn compact_folders() {
+        let items = vec![
+            TreeItem::new(
+                "s",
+                "src",
+                vec![
+                    TreeItem::new(
+                        "m",
+                        "main",
+                        vec![
+                            TreeItem::new("j", "java", vec![TreeItem::new_leaf("a", "A")]).unwrap(),
+                        ],
+                    )
+                    .unwrap(),
+                ],
+            )
+            .unwrap(),
+            TreeItem::new_leaf("r", "README"),
+        ];
+        let tree = Tree::new(items).unwrap().compact_folders(true);
+        let mut state = ExplorerState::default();
+        state.expand(vec!["s", "m", "j"]);
+        let buffer = render_tree(&tree, 16, 3, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "▼ src/main/java ",
+            "    A           ",
+            "  README        ",
+        ]);
+        assert_eq!(buffer, expected);
+
+        assert!(state.click_at(Position::new(7, 0)));
+        assert_eq!(state.selected, ["s", "m"]);
+        let buffer = render_tree(&tree, 16, 3, &mut state);
+        let highlighted = (0..16)
+            .filter(|x| buffer[(*x, 0)].modifier.contains(Modifier::REVERSED))
+            .collect::<Vec<_>>();
+        assert_eq!(highlighted, [6, 7, 8, 9]);
+
+        assert!(state.select_next());
+        assert_eq!(state.selected, ["s", "m", "j", "a"]);
+    }
+
+    #[test]
+    fn compact_folders_toggle_from_outer_segment() {
+        let items = vec![
+            TreeItem::new(
+                "s",
+                "src",
+                vec![TreeItem::new("m", "main", vec![TreeItem::new_leaf("a", "A")]).unwrap()],
+            )
+            .unwrap(),
+        ];
+        let tree = Tree::new(items).unwrap().compact_folders(true);
+        let mut state = ExplorerState::default();
+        let rows = |state: &mut ExplorerState<&'static str>| {
+            let buffer = render_tree(&tree, 12, 2, state);
+            (0..2)
+                .map(|y| (0..12).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(&mut state), ["▶ src/main  ", "            "]);
+
+        // Select the outer segment, clicking it again toggles the whole row
+        assert!(state.click_at(Position::new(3, 0)));
+        assert_eq!(state.selected, ["s"]);
+        assert!(state.click_at(Position::new(3, 0)));
+        assert_eq!(rows(&mut state), ["▼ src/main  ", "    A       "]);
+
+        assert!(state.toggle_selected());
+        assert_eq!(rows(&mut state), ["▶ src/main  ", "            "]);
+    }
+
+    #[test]
+    fn sticky_headers() {
+        let tree = Tree::new(TreeItem::example()).unwrap().sticky_headers(2);
+        let mut state = ExplorerState::default();
+        state.expand(vec!["b"]);
+        state.expand(vec!["b", "d"]);
+
+        // The folder being scrolled through stays above its children
+        state.offset = 3;
+        let buffer = render_tree(&tree, 15, 4, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "▼ Bravo        ",
+            "  ▼ Delta      ",
+            "      Foxtrot  ",
+            "    Golf       ",
+        ]);
+        assert_eq!(buffer, expected);
+
+        state.offset = 4;
+        let buffer = render_tree(&tree, 15, 4, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "▼ Bravo        ",
+            "  ▼ Delta      ",
+            "    Golf       ",
+            "  Hotel        ",
+        ]);
+        assert_eq!(buffer, expected);
+
+        state.offset = 5;
+        let buffer = render_tree(&tree, 15, 4, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "▼ Bravo        ",
+            "    Golf       ",
+            "  Hotel        ",
+            "               ",
+        ]);
+        assert_eq!(buffer, expected);
+
+        assert!(state.click_at(Position::new(3, 0)));
+        assert_eq!(state.selected, ["b"]);
+        _ = render_tree(&tree, 15, 4, &mut state);
+        assert_eq!(state.offset, 1);
+    }
+
+    #[test]
+    fn sticky_headers_stack() {
+        let items = vec![
+            TreeItem::new(
+                "b",
+                "Bravo",
+                vec![
+                    TreeItem::new(
+                        "d",
+                        "Delta",
+                        vec![
+                            TreeItem::new_leaf("e", "Echo"),
+                            TreeItem::new_leaf("f", "Foxtrot"),
+                            TreeItem::new_leaf("i", "India"),
+                        ],
+                    )
+                    .unwrap(),
+                ],
+            )
+            .unwrap(),
+        ];
+        let tree = Tree::new(items).unwrap().sticky_headers(2);
+        let mut state = ExplorerState::default();
+        state.expand(vec!["b"]);
+        state.expand(vec!["b", "d"]);
+        state.offset = 2;
+        let buffer = render_tree(&tree, 15, 3, &mut state);
+        #[rustfmt::skip]
+        let expected = Buffer::with_lines([
+            "▼ Bravo        ",
+            "  ▼ Delta      ",
+            "      India    ",
+        ]);
+        assert_eq!(buffer, expected);
+        assert_eq!(
+            state.rendered_at(Position::new(0, 1)),
+            Some(["b", "d"].as_slice())
+        );
+    }
 }
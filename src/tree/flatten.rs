@@ -1,19 +1,70 @@
+use core::fmt::Debug;
 use core::hash::Hash;
+use std::borrow::Cow;
 use std::collections::HashSet;
-use std::fmt::Debug;
+
+use ratatui::text::{Line, Span, Text};
 
 use super::item::TreeItem;
 
+/// Separator between the folder names of a compacted row.
+pub const COMPACT_SEPARATOR: &str = "/";
+
 /// A flattened item of all visible [`TreeItem`]s.
 pub struct Flattened<'text, Identifier> {
     pub identifier: Vec<Identifier>,
     pub item: TreeItem<'text, Identifier>,
+    /// Texts of the ancestors merged into this row by [`flatten_compact`], outermost first
+    pub compacted: Vec<Text<'text>>,
+    /// Amount of ancestors merged into rows, they do not add indentation
+    pub merged_levels: usize,
 }
 
 impl<'text, Identifier> Flattened<'text, Identifier> {
     /// Zero based depth. Depth 0 means top level with 0 indentation.
     pub fn depth(&self) -> usize {
-        self.identifier.len() - 1
+        self.identifier.len() - 1 - self.merged_levels
+    }
+
+    /// Text of the row, which joins the compacted ancestors and the item like `main/java/com`.
+    #[must_use]
+    pub fn text(&self) -> Cow<'_, Text<'text>> {
+        if self.compacted.is_empty() {
+            return Cow::Borrowed(&self.item.text);
+        }
+
+        let mut spans = Vec::new();
+        for segment in &self.compacted {
+            for line in segment.lines.iter().take(1) {
+                spans.extend(line.spans.iter().map(|span| {
+                    let style = segment.style.patch(line.style).patch(span.style);
+                    Span::styled(span.content.clone(), style)
+                }));
+            }
+            spans.push(Span::raw(COMPACT_SEPARATOR));
+        }
+
+        let mut text = self.item.text.clone();
+        if let Some(line) = text.lines.first_mut() {
+            spans.append(&mut line.spans);
+            line.spans = spans;
+        } else {
+            text.lines.push(Line::from(spans));
+        }
+        Cow::Owned(text)
+    }
+
+    /// Identifiers and widths of the segments of a compacted row, outermost first.
+    ///
+    /// Rows which are not compacted consist of a single segment.
+    pub fn segments(&self) -> impl Iterator<Item = (&[Identifier], usize)> {
+        let first_segment = self.identifier.len() - self.compacted.len();
+        self.compacted
+            .iter()
+            .map(Text::width)
+            .chain([self.item.text.width()])
+            .enumerate()
+            .map(move |(index, width)| (&self.identifier[..first_segment + index], width))
     }
 }
 
@@ -21,7 +72,7 @@ impl<'text, Identifier> Flattened<'text, Identifier> {
 pub fn flatten<'text, Identifier>(
     open_identifiers: &HashSet<Vec<Identifier>>,
     items: Vec<TreeItem<'text, Identifier>>,
-    current: &Vec<Identifier>,
+    current: &[Identifier],
 ) -> Vec<Flattened<'text, Identifier>>
 where
     Identifier: Clone + PartialEq + Eq + Hash + Debug,
@@ -47,6 +98,8 @@ where
         result.push(Flattened {
             identifier: child_identifier.clone(),
             item: item.clone(),
+            compacted: Vec::new(),
+            merged_levels: 0,
         });
 
         if let Some(mut child_result) = child_result {
@@ -57,6 +110,72 @@ where
     result
 }
 
+/// Get a flat list of all visible [`TreeItem`]s with compact folders.
+///
+/// Chains of items which each contain exactly one child with children of its own
+/// (like `src/main/java/com`) are merged into a single row.
+/// The row uses the identifier of the innermost item of the chain,
+/// so its children are visible when that identifier is expanded.
+pub fn flatten_compact<'text, Identifier>(
+    open_identifiers: &HashSet<Vec<Identifier>>,
+    items: &[TreeItem<'text, Identifier>],
+    current: &[Identifier],
+) -> Vec<Flattened<'text, Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
+{
+    flatten_compact_merged(open_identifiers, items, current, 0)
+}
+
+fn flatten_compact_merged<'text, Identifier>(
+    open_identifiers: &HashSet<Vec<Identifier>>,
+    items: &[TreeItem<'text, Identifier>],
+    current: &[Identifier],
+    merged_levels: usize,
+) -> Vec<Flattened<'text, Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + Hash + Debug,
+{
+    let mut result = Vec::new();
+
+    for mut item in items {
+        let mut child_identifier = current.to_vec();
+        child_identifier.push(item.identifier.clone());
+
+        let mut compacted = Vec::new();
+        while let [child] = item.children.as_slice() {
+            if child.children.is_empty() {
+                break;
+            }
+            compacted.push(item.text.clone());
+            child_identifier.push(child.identifier.clone());
+            item = child;
+        }
+        let merged_levels = merged_levels + compacted.len();
+
+        let child_result = if open_identifiers.contains(&child_identifier) {
+            flatten_compact_merged(
+                open_identifiers,
+                &item.children,
+                &child_identifier,
+                merged_levels,
+            )
+        } else {
+            Vec::new()
+        };
+
+        result.push(Flattened {
+            identifier: child_identifier,
+            item: item.clone(),
+            compacted,
+            merged_levels,
+        });
+        result.extend(child_result);
+    }
+
+    result
+}
+
 #[test]
 fn depth_works() {
     let mut open = HashSet::new();
@@ -108,3 +227,68 @@ fn flatten_all_open() {
     open.insert(vec!["b", "d"]);
     flatten_works(&open, &["a", "b", "c", "d", "e", "f", "g", "h"]);
 }
+
+#[test]
+fn flatten_compact_merges_single_child_chains() {
+    let items = vec![
+        TreeItem::new(
+            "src",
+            "src",
+            vec![
+                TreeItem::new(
+                    "main",
+                    "main",
+                    vec![
+                        TreeItem::new("java", "java", vec![TreeItem::new_leaf("A", "A.java")])
+                            .unwrap(),
+                    ],
+                )
+                .unwrap(),
+            ],
+        )
+        .unwrap(),
+        TreeItem::new_leaf("readme", "README.md"),
+    ];
+    let mut open = HashSet::new();
+    open.insert(vec!["src", "main", "java"]);
+    let result = flatten_compact(&open, &items, &[]);
+    let rows = result
+        .iter()
+        .map(|flattened| (flattened.text().to_string(), flattened.depth()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        [
+            ("src/main/java".to_string(), 0),
+            ("A.java".to_string(), 1),
+            ("README.md".to_string(), 0),
+        ]
+    );
+    assert_eq!(result[1].identifier, ["src", "main", "java", "A"]);
+
+    let segments = result[0].segments().collect::<Vec<_>>();
+    assert_eq!(
+        segments,
+        [
+            (["src"].as_slice(), 3),
+            (["src", "main"].as_slice(), 4),
+            (["src", "main", "java"].as_slice(), 4),
+        ]
+    );
+}
+
+#[test]
+fn flatten_compact_keeps_folders_with_multiple_children() {
+    let mut open = HashSet::new();
+    open.insert(vec!["b"]);
+    let items = TreeItem::example();
+    let compact = flatten_compact(&open, &items, &[])
+        .into_iter()
+        .map(|flattened| flattened.identifier)
+        .collect::<Vec<_>>();
+    let regular = flatten(&open, items, &[])
+        .into_iter()
+        .map(|flattened| flattened.identifier)
+        .collect::<Vec<_>>();
+    assert_eq!(compact, regular);
+}
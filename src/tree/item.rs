@@ -58,7 +58,6 @@ where
         &self.identifier
     }
 
-    #[allow(clippy::missing_const_for_fn)] // False positive
     pub fn children(&self) -> &[Self] {
         &self.children
     }
@@ -104,7 +103,6 @@ where
 
 impl TreeItem<'static, &'static str> {
     #[cfg(test)]
-
     pub(crate) fn example() -> Vec<Self> {
         vec![
             Self::new_leaf("a", "Alfa"),
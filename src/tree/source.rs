@@ -0,0 +1,230 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use std::collections::HashMap;
+use std::io;
+
+use ratatui::text::Text;
+
+use crate::explorer::state::ExplorerState;
+use crate::tree::Tree;
+use crate::tree::item::TreeItem;
+
+/// Hierarchical data shown in a [`Tree`] without being files,
+/// like the values of a JSON document, the symbols of a source file or running processes.
+///
+/// Nodes are identified by the chain of identifiers from the top level node down to them,
+/// like in an [`ExplorerState`]. Children are only asked for when needed, see [`LazyTree`].
+pub trait TreeSource {
+    type Identifier: Clone + PartialEq + Eq + Hash + Debug;
+
+    /// The top level nodes.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the data can not be read.
+    fn roots(&self) -> io::Result<Vec<Self::Identifier>>;
+
+    /// The children of the node, empty for a leaf.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the data can not be read.
+    fn children(&self, identifier: &[Self::Identifier]) -> io::Result<Vec<Self::Identifier>>;
+
+    /// The text of the node.
+    fn text(&self, identifier: &[Self::Identifier]) -> Text<'static>;
+}
+
+/// A [`Tree`] of a [`TreeSource`] which loads the children of nodes as they become visible.
+///
+/// The children of visible nodes are loaded one level ahead, so they show up as expandable.
+/// Call [`load`](Self::load) after the expanded nodes of the state changed, like on every tick.
+#[derive(Debug, Clone)]
+pub struct LazyTree<S>
+where
+    S: TreeSource,
+{
+    pub source: S,
+    pub tree: Tree<'static, S::Identifier>,
+    /// Children loaded so far by the identifier of their parent, the roots by the empty identifier
+    loaded: HashMap<Vec<S::Identifier>, Vec<S::Identifier>>,
+}
+
+impl<S> LazyTree<S>
+where
+    S: TreeSource,
+{
+    /// Create a `LazyTree` with the top level nodes of the source loaded.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the source can not be read or identifiers are duplicated.
+    pub fn new(source: S) -> io::Result<Self> {
+        let mut lazy = Self {
+            source,
+            tree: Tree::new(Vec::new())?,
+            loaded: HashMap::new(),
+        };
+        lazy.load(&ExplorerState::default())?;
+        Ok(lazy)
+    }
+
+    /// Load the children of the nodes visible with the given state and rebuild the tree.
+    ///
+    /// Returns `true` when something was loaded.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the source can not be read or identifiers are duplicated.
+    pub fn load(&mut self, state: &ExplorerState<S::Identifier>) -> io::Result<bool> {
+        let mut loaded_any = self.ensure_loaded(&[])?;
+        loaded_any |= self.load_below(&[], state)?;
+
+        if loaded_any {
+            let items = self.items(&[])?;
+            self.tree.set_items(items)?;
+        }
+        Ok(loaded_any)
+    }
+
+    /// Forget the children of the node and everything below it, the whole tree when empty.
+    /// They are loaded again on the next [`load`](Self::load).
+    pub fn invalidate(&mut self, identifier: &[S::Identifier]) {
+        self.loaded
+            .retain(|parent, _| !parent.starts_with(identifier));
+    }
+
+    fn ensure_loaded(&mut self, identifier: &[S::Identifier]) -> io::Result<bool> {
+        if self.loaded.contains_key(identifier) {
+            return Ok(false);
+        }
+        let children = if identifier.is_empty() {
+            self.source.roots()?
+        } else {
+            self.source.children(identifier)?
+        };
+        self.loaded.insert(identifier.to_vec(), children);
+        Ok(true)
+    }
+
+    /// Load the children of the visible nodes below the parent, one level ahead.
+    fn load_below(
+        &mut self,
+        parent: &[S::Identifier],
+        state: &ExplorerState<S::Identifier>,
+    ) -> io::Result<bool> {
+        let mut loaded_any = false;
+        let children = self.loaded.get(parent).cloned().unwrap_or_default();
+        for child in children {
+            let mut identifier = parent.to_vec();
+            identifier.push(child);
+            loaded_any |= self.ensure_loaded(&identifier)?;
+            if state.expanded.contains(&identifier) {
+                loaded_any |= self.load_below(&identifier, state)?;
+            }
+        }
+        Ok(loaded_any)
+    }
+
+    fn items(&self, parent: &[S::Identifier]) -> io::Result<Vec<TreeItem<'static, S::Identifier>>> {
+        let Some(children) = self.loaded.get(parent) else {
+            return Ok(Vec::new());
+        };
+        children
+            .iter()
+            .map(|child| {
+                let mut identifier = parent.to_vec();
+                identifier.push(child.clone());
+                let text = self.source.text(&identifier);
+                TreeItem::new(child.clone(), text, self.items(&identifier)?)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidgetRef as _;
+
+    use super::*;
+
+    /// Numbers below 100 have the children `n1` and `n2`, like 1 → 11, 12.
+    #[derive(Debug, Default)]
+    struct Numbers {
+        loads: Cell<usize>,
+    }
+
+    impl TreeSource for Numbers {
+        type Identifier = u32;
+
+        fn roots(&self) -> io::Result<Vec<u32>> {
+            Ok(vec![1, 2])
+        }
+
+        fn children(&self, identifier: &[u32]) -> io::Result<Vec<u32>> {
+            self.loads.set(self.loads.get() + 1);
+            let parent = identifier.last().copied().unwrap_or_default();
+            Ok(if parent < 100 {
+                vec![parent * 10 + 1, parent * 10 + 2]
+            } else {
+                Vec::new()
+            })
+        }
+
+        fn text(&self, identifier: &[u32]) -> Text<'static> {
+            Text::raw(
+                identifier
+                    .last()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            )
+        }
+    }
+
+    fn render(lazy: &LazyTree<Numbers>, state: &mut ExplorerState<u32>) -> Vec<String> {
+        let area = Rect::new(0, 0, 8, 4);
+        let mut buffer = Buffer::empty(area);
+        lazy.tree.render_ref(area, &mut buffer, state);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loads_visible_nodes() {
+        let mut lazy = LazyTree::new(Numbers::default()).unwrap();
+        // One level ahead of the visible roots
+        assert_eq!(lazy.source.loads.get(), 2);
+
+        let mut state = ExplorerState::default();
+        assert_eq!(
+            render(&lazy, &mut state),
+            ["▶ 1    ", "▶ 2    ", "", ""].map(|row| format!("{row:8}"))
+        );
+
+        state.select_first();
+        state.toggle_selected();
+        assert!(lazy.load(&state).unwrap());
+        assert_eq!(lazy.source.loads.get(), 4);
+        assert!(!lazy.load(&state).unwrap());
+
+        assert_eq!(
+            render(&lazy, &mut state),
+            ["▼ 1    ", "  ▶ 11 ", "  ▶ 12 ", "▶ 2    "].map(|row| format!("{row:8}"))
+        );
+        state.select_next();
+        assert_eq!(state.selected, [1, 11]);
+
+        lazy.invalidate(&[1]);
+        assert!(lazy.load(&state).unwrap());
+        assert_eq!(lazy.source.loads.get(), 7);
+    }
+}
@@ -0,0 +1,262 @@
+use ratatui::style::Style;
+use ratatui::text::{Line, Span, Text};
+use unicode_width::UnicodeWidthChar as _;
+
+/// Symbol inserted where text has been cut away.
+pub const ELLIPSIS: &str = "\u{2026}";
+
+/// How the text of a [`TreeItem`](super::item::TreeItem) is shortened when it does not fit
+/// into the width available on its row.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Truncation {
+    /// Cut the text at the right edge without any indication.
+    #[default]
+    Clip,
+    /// Keep the start of the text and end it with an ellipsis: `very_long_fi…`
+    End,
+    /// Keep the start and the end of the text with an ellipsis in between: `very_lo…name.rs`
+    Middle,
+    /// Like [`End`](Self::End), but keep the file extension visible: `very_long_….rs`
+    ///
+    /// Falls back to [`End`](Self::End) when there is no extension or it would not fit.
+    KeepExtension,
+}
+
+impl Truncation {
+    /// Shorten every line of the given text to fit into `max_width` columns.
+    #[must_use]
+    pub fn truncate_text<'text>(self, text: &Text<'text>, max_width: usize) -> Text<'text> {
+        let mut truncated = text.clone();
+        truncated.lines = text
+            .lines
+            .iter()
+            .map(|line| self.truncate_line(line, max_width))
+            .collect();
+        truncated
+    }
+
+    /// Shorten the given line to fit into `max_width` columns.
+    ///
+    /// Widths are display widths, so wide characters (like CJK) count as two columns.
+    /// The styles of the spans are kept, the ellipsis takes the style of the text it replaces.
+    #[must_use]
+    pub fn truncate_line<'text>(self, line: &Line<'text>, max_width: usize) -> Line<'text> {
+        if self == Self::Clip || line.width() <= max_width {
+            return line.clone();
+        }
+
+        let chars = line
+            .spans
+            .iter()
+            .flat_map(|span| span.content.chars().map(|char| (span.style, char)))
+            .collect::<Vec<_>>();
+
+        let budget = max_width.saturating_sub(ELLIPSIS.chars().count());
+        let extension_width = match self {
+            Self::KeepExtension => extension_width(&chars),
+            Self::Clip | Self::End | Self::Middle => 0,
+        };
+        let keeps_tail = match self {
+            Self::Middle => true,
+            Self::KeepExtension => extension_width > 0 && extension_width < budget,
+            Self::Clip | Self::End => false,
+        };
+        let head_width = match self {
+            Self::Middle => budget.div_ceil(2),
+            _ if keeps_tail => budget - extension_width,
+            Self::Clip | Self::End | Self::KeepExtension => budget,
+        };
+
+        let (head, used_width) = chars_within(chars.iter(), head_width);
+        // Wide chars might not fill the head completely, the tail gets the rest
+        let tail_width = if keeps_tail { budget - used_width } else { 0 };
+        let (tail, _) = chars_within(chars.iter().rev(), tail_width);
+
+        let ellipsis_style = chars
+            .get(head.saturating_sub(1))
+            .map_or_else(Style::new, |(style, _)| *style);
+
+        let mut spans = Vec::new();
+        push_chars(&mut spans, &chars[..head]);
+        if max_width > 0 {
+            let ellipsis = ELLIPSIS.chars().map(|char| (ellipsis_style, char));
+            push_chars(&mut spans, &ellipsis.collect::<Vec<_>>());
+        }
+        push_chars(&mut spans, &chars[chars.len() - tail..]);
+
+        let mut truncated = line.clone();
+        truncated.spans = spans;
+        truncated
+    }
+}
+
+/// Cut the given amount of columns from the start of the line, as when scrolled horizontally.
+///
+/// A wide char which is only partly scrolled out of view is replaced by spaces.
+pub(crate) fn skip_columns<'text>(line: &Line<'text>, columns: usize) -> Line<'text> {
+    if columns == 0 {
+        return line.clone();
+    }
+
+    let mut skipped = 0;
+    let mut spans = Vec::new();
+    for span in &line.spans {
+        if skipped >= columns {
+            spans.push(span.clone());
+            continue;
+        }
+
+        let mut content = String::new();
+        for char in span.content.chars() {
+            if skipped < columns {
+                skipped += char.width().unwrap_or(0);
+                content.extend(core::iter::repeat_n(' ', skipped.saturating_sub(columns)));
+            } else {
+                content.push(char);
+            }
+        }
+        if !content.is_empty() {
+            spans.push(Span::styled(content, span.style));
+        }
+    }
+
+    let mut skipped_line = line.clone();
+    skipped_line.spans = spans;
+    skipped_line
+}
+
+/// Width of the extension including its dot. Leading dots (hidden files) are not an extension.
+fn extension_width(chars: &[(Style, char)]) -> usize {
+    chars
+        .iter()
+        .rposition(|(_, char)| *char == '.')
+        .filter(|position| *position > 0)
+        .map_or(0, |position| {
+            chars[position..]
+                .iter()
+                .map(|(_, char)| char.width().unwrap_or(0))
+                .sum()
+        })
+}
+
+/// Amount of chars that fit into the given width and the width they occupy.
+fn chars_within<'chars>(
+    chars: impl Iterator<Item = &'chars (Style, char)>,
+    max_width: usize,
+) -> (usize, usize) {
+    let mut count = 0;
+    let mut width = 0;
+    for (_, char) in chars {
+        let char_width = char.width().unwrap_or(0);
+        if width + char_width > max_width {
+            break;
+        }
+        count += 1;
+        width += char_width;
+    }
+    (count, width)
+}
+
+/// Append the chars as spans, merging neighbours with the same style.
+fn push_chars(spans: &mut Vec<Span<'static>>, chars: &[(Style, char)]) {
+    for (style, char) in chars {
+        match spans.last_mut() {
+            Some(span) if span.style == *style => span.content.to_mut().push(*char),
+            _ => spans.push(Span::styled(char.to_string(), *style)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Modifier;
+
+    #[track_caller]
+    fn truncated(truncation: Truncation, text: &str, max_width: usize) -> String {
+        truncation
+            .truncate_line(&Line::from(text), max_width)
+            .to_string()
+    }
+
+    #[test]
+    fn fitting_text_is_untouched() {
+        for truncation in [
+            Truncation::Clip,
+            Truncation::End,
+            Truncation::Middle,
+            Truncation::KeepExtension,
+        ] {
+            assert_eq!(truncated(truncation, "main.rs", 7), "main.rs");
+        }
+    }
+
+    #[test]
+    fn end() {
+        assert_eq!(
+            truncated(Truncation::End, "very_long_name.rs", 8),
+            "very_lo…"
+        );
+    }
+
+    #[test]
+    fn middle() {
+        assert_eq!(
+            truncated(Truncation::Middle, "very_long_name.rs", 8),
+            "very….rs"
+        );
+        assert_eq!(
+            truncated(Truncation::Middle, "very_long_name.rs", 9),
+            "very…e.rs"
+        );
+    }
+
+    #[test]
+    fn keep_extension() {
+        assert_eq!(
+            truncated(Truncation::KeepExtension, "very_long_name.rs", 10),
+            "very_l….rs"
+        );
+        assert_eq!(
+            truncated(Truncation::KeepExtension, ".very_long_hidden", 8),
+            ".very_l…"
+        );
+        assert_eq!(
+            truncated(Truncation::KeepExtension, "name.extension", 8),
+            "name.ex…"
+        );
+    }
+
+    #[test]
+    fn respects_display_width() {
+        assert_eq!(truncated(Truncation::End, "日本語のファイル", 7), "日本語…");
+        assert_eq!(
+            truncated(Truncation::Middle, "日本語のファイル", 7),
+            "日…イル"
+        );
+    }
+
+    #[test]
+    fn skip_columns_splits_wide_chars() {
+        let line = Line::from("日本語");
+        assert_eq!(skip_columns(&line, 2).to_string(), "本語");
+        assert_eq!(skip_columns(&line, 3).to_string(), " 語");
+        assert_eq!(skip_columns(&line, 7).to_string(), "");
+    }
+
+    #[test]
+    fn keeps_span_styles() {
+        let line = Line::from(vec![
+            Span::styled("bold", Style::new().add_modifier(Modifier::BOLD)),
+            Span::raw("_and_plain"),
+        ]);
+        let truncated = Truncation::End.truncate_line(&line, 6);
+        assert_eq!(
+            truncated.spans,
+            [
+                Span::styled("bold", Style::new().add_modifier(Modifier::BOLD)),
+                Span::raw("_…"),
+            ]
+        );
+    }
+}